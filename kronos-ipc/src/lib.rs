@@ -4,19 +4,65 @@
 //! and perfect for this use case.
 
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixStream;
 
 /// Commands that kronosctl can send to kronos
+///
+/// Every connection opens with `Hello` carrying the client's
+/// `PROTOCOL_VERSION`; the server answers with its own `Response::Hello`, or
+/// an error if the majors differ, before the real command is sent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Command {
+    Hello { version: String },
     Start,
     Pause,
     Resume,
     Stop,
     Reset,
     Status,
-    AddTask { description: String },
+    /// Fields left as `None` get the same defaults as a task added in the
+    /// TUI. Unknown categories are kept as custom ones.
+    AddTask {
+        description: String,
+        #[serde(default)]
+        category: Option<String>,
+        #[serde(default)]
+        minutes: Option<i64>,
+        #[serde(default)]
+        priority: Option<String>,
+    },
     ListTasks,
+    /// `ListTasks` narrowed to tasks with fewer completed Pomodoros than
+    /// estimated when `incomplete_pomodoros` is set.
+    FilterTasks { incomplete_pomodoros: bool },
+    ImportTasks { format: ImportFormat, data: String },
+    History,
+    Ping,
+    StartAll { category: Option<String> },
+    CategoryBreakdown,
+    SelectTask { id: u32 },
+    Subscribe,
+    Keybindings,
+    Version,
+    ListPresets,
+    /// Sets the timer of task `id` to the named preset's length
+    ApplyPreset { id: u32, preset: String },
+    /// Ticks or unticks subtask `subtask_index` (from 0) of task `task_id`
+    ToggleSubtask { task_id: u32, subtask_index: usize },
+    /// The task to work on next, see `Response::Suggestion`
+    SuggestTask,
+    /// A single task, answered with `Response::Task`
+    GetTask { id: u32 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ImportFormat {
+    Json,
+    Csv,
 }
 
 /// Responses from kronos back to kronosctl
@@ -25,9 +71,40 @@ pub enum Response {
     Ok,
     Status(TimerStatus),
     Tasks(Vec<Task>),
+    Task(Task),
+    Imported { imported: usize, skipped: usize },
+    History(Vec<CommandLogEntry>),
+    Hello { version: String },
+    Pong { version: String },
+    Started { count: usize },
+    CategoryBreakdown(Vec<(String, u64)>), // minutes per category
+    Event(TimerEvent),
+    Cheatsheet(String), // Markdown
+    /// Version of the kronos binary and of the wire protocol it speaks
+    Version { version: String, protocol: String },
+    Presets(Vec<(String, i64)>), // name and minutes, by name
+    /// Highest priority incomplete task, oldest first; `None` if all are done
+    Suggestion(Option<Task>),
     Error(String),
 }
 
+/// Timer changes streamed to `Command::Subscribe` connections. Task timers
+/// are identified by task id; id 0 is the global timer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TimerEvent {
+    Started { id: u32 },
+    Paused { id: u32 },
+    Stopped { id: u32 },
+    Completed { id: u32 },
+}
+
+/// A command the server processed, oldest first in `Response::History`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLogEntry {
+    pub timestamp: u64, // seconds since the Unix epoch
+    pub command: Command,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimerStatus {
     pub state: TimerState,
@@ -47,6 +124,16 @@ pub struct Task {
     pub id: u32,
     pub description: String,
     pub completed: bool,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub priority: String,
+    #[serde(default)]
+    pub elapsed_seconds: u64,
+    #[serde(default)]
+    pub estimated_pomodoros: u32,
+    #[serde(default)]
+    pub completed_pomodoros: u32,
 }
 
 #[derive(Error, Debug)]
@@ -59,6 +146,126 @@ pub enum IpcError {
     
     #[error("Connection refused - is kronos running?")]
     ConnectionRefused,
+
+    #[error("Handshake failed: {0}")]
+    Handshake(String),
 }
 
 pub const SOCKET_PATH: &str = "/tmp/kronos.sock";
+
+/// Wire protocol version as `major.minor`; only the major must match
+pub const PROTOCOL_VERSION: &str = "1.0";
+
+/// Whether a peer speaking `version` can talk to us
+pub fn is_compatible(version: &str) -> bool {
+    let major = |v: &str| v.split('.').next().unwrap_or("").to_string();
+    major(version) == major(PROTOCOL_VERSION)
+}
+
+/// Sends one command to the running kronos and waits for its response
+pub async fn send_command(cmd: Command) -> Result<Response, IpcError> {
+    send_command_to(SOCKET_PATH, cmd).await
+}
+
+/// `send_command` to a kronos listening on `socket` rather than `SOCKET_PATH`
+pub async fn send_command_to(socket: impl AsRef<Path>, cmd: Command) -> Result<Response, IpcError> {
+    let (mut reader, mut writer) = connect(socket.as_ref()).await?;
+
+    // Send command
+    let msg = serde_json::to_vec(&cmd)?;
+    writer.write_all(&msg).await?;
+    writer.write_all(b"\n").await?;
+
+    // Read response; the server closes the connection once it's written
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).await?;
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+/// An open `Command::Subscribe` connection
+pub struct Subscription {
+    reader: BufReader<OwnedReadHalf>,
+    // Dropping the write half would shut the connection down
+    _writer: OwnedWriteHalf,
+}
+
+impl Subscription {
+    /// Waits for the next event; `None` once kronos closes the connection
+    pub async fn next_event(&mut self) -> Result<Option<TimerEvent>, IpcError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        match serde_json::from_str(&line)? {
+            Response::Event(event) => Ok(Some(event)),
+            Response::Error(e) => Err(IpcError::Handshake(e)),
+            other => Err(IpcError::Handshake(format!("unexpected reply {:?}", other))),
+        }
+    }
+}
+
+/// Asks the running kronos to stream timer events over a kept-open connection
+pub async fn subscribe() -> Result<Subscription, IpcError> {
+    let (mut reader, mut writer) = connect(Path::new(SOCKET_PATH)).await?;
+    writer
+        .write_all(&serde_json::to_vec(&Command::Subscribe)?)
+        .await?;
+    writer.write_all(b"\n").await?;
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    match serde_json::from_str(&line)? {
+        Response::Ok => Ok(Subscription {
+            reader,
+            _writer: writer,
+        }),
+        Response::Error(e) => Err(IpcError::Handshake(e)),
+        other => Err(IpcError::Handshake(format!("unexpected reply {:?}", other))),
+    }
+}
+
+/// Connects to kronos and performs the `Hello` handshake
+async fn connect(socket: &Path) -> Result<(BufReader<OwnedReadHalf>, OwnedWriteHalf), IpcError> {
+    let stream = UnixStream::connect(socket)
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused => {
+                IpcError::ConnectionRefused
+            }
+            _ => IpcError::Io(e),
+        })?;
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    // Agree on a protocol version before sending anything else
+    let hello = Command::Hello {
+        version: PROTOCOL_VERSION.to_string(),
+    };
+    writer.write_all(&serde_json::to_vec(&hello)?).await?;
+    writer.write_all(b"\n").await?;
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    match serde_json::from_str(&line)? {
+        Response::Hello { .. } => {}
+        Response::Error(e) => return Err(IpcError::Handshake(e)),
+        other => return Err(IpcError::Handshake(format!("unexpected reply {:?}", other))),
+    }
+    Ok((reader, writer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_major_is_compatible() {
+        assert!(is_compatible(PROTOCOL_VERSION));
+        assert!(is_compatible("1.7"));
+    }
+
+    #[test]
+    fn other_majors_are_rejected() {
+        assert!(!is_compatible("2.0"));
+        assert!(!is_compatible("0.9"));
+        assert!(!is_compatible(""));
+    }
+}
@@ -1,18 +1,39 @@
-use crate::config::Config;
-use chrono::{DateTime, Duration, Local};
-use kronos_ipc::TimerState;
+use crate::config::{self, CategorySort, Config, Icons, StartupView, Theme};
+use crate::fuzzy::fuzzy_filter;
+use crate::hooks;
+use crate::keys;
+use crate::persistence::Persistence;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use kronos_ipc::{CommandLogEntry, TimerEvent, TimerState};
 use ratatui::layout::Rect;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tachyonfx::{fx, EffectManager, Motion};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+use tachyonfx::{fx, CellFilter, Effect, EffectManager, Motion};
+use tokio::sync::broadcast;
 
 #[derive(Serialize, Deserialize)]
 pub struct App {
     pub tasks: Vec<Task>,
     pub selected_task: usize,
+    /// Id of the selected task, so the selection survives a reordered list
+    /// across restarts. Kept current by `reconcile_selection`.
+    #[serde(default)]
+    pub selected_task_id: Option<u32>,
     pub next_task_id: u32,
     pub global_timer: Timer,
     pub presets: HashMap<String, i64>,
+    /// Edits that `undo` can reverse, oldest first, at most `UNDO_LIMIT`.
+    #[serde(default)]
+    pub undo_stack: Vec<Action>,
+    /// Undone edits that `redo` can apply again, most recent last.
+    #[serde(default)]
+    pub redo_stack: Vec<Action>,
     #[serde(skip)]
     pub mode: AppMode,
     #[serde(skip)]
@@ -20,36 +41,203 @@ pub struct App {
     #[serde(skip)]
     pub notifications_sent: Vec<u32>,
     #[serde(skip)]
+    pub notification_failures: u32,
+    #[serde(skip)]
+    pub notifications_unavailable: bool,
+    #[serde(skip)]
     pub config: Config,
     #[serde(skip, default = "default_effect_manager")]
     pub effect_manager: EffectManager<u32>,
     #[serde(skip)]
     pub should_quit: bool,
+    #[serde(skip)]
+    pub compact_view: bool,
+    /// List deferred tasks alongside today's.
+    #[serde(skip)]
+    pub show_deferred: bool,
+    /// List only tasks with an active timer.
+    #[serde(skip)]
+    pub show_only_active: bool,
     pub stats: Stats,
     #[serde(skip)]
     pub category_list_state: ratatui::widgets::ListState,
+    #[serde(skip)]
+    pub preset_list_state: ratatui::widgets::ListState,
+    #[serde(skip)]
+    pub command_log: VecDeque<CommandLogEntry>,
+    #[serde(skip)]
+    pub work_started: Option<DateTime<Local>>,
+    #[serde(skip)]
+    pub last_worked: Option<DateTime<Local>>,
+    #[serde(skip)]
+    pub break_suggested: bool,
+    #[serde(skip)]
+    pub selection_anchor: Option<u32>,
+    #[serde(skip)]
+    pub last_running_pulse: Option<Instant>,
+    #[serde(skip)]
+    pub status_message: Option<String>,
+    #[serde(skip)]
+    pub theme_editor: ThemeEditor,
+    #[serde(skip)]
+    pub tip_index: usize,
+    #[serde(skip)]
+    pub last_tip_change: Option<Instant>,
+    /// Where timer events go for `Command::Subscribe` clients.
+    #[serde(skip)]
+    pub events: Option<broadcast::Sender<TimerEvent>>,
+    /// Set when a completion should ring the terminal bell; the render loop
+    /// owns the terminal and takes it with `take_bell`.
+    #[serde(skip)]
+    pub bell_pending: bool,
+    /// Completion glyph animations in progress, by task id.
+    #[serde(skip)]
+    pub done_animations: HashMap<u32, FrameAnimation>,
+    /// Brief message about the latest completion and when it was raised. A
+    /// newer completion replaces it.
+    #[serde(skip)]
+    pub toast: Option<(String, Instant)>,
+    /// When `AppMode::StartupAnimation` gives way to the startup view.
+    #[serde(skip)]
+    pub startup_ends_at: Option<Instant>,
+    /// Subtask lines entered so far in `AppMode::SplittingTask`.
+    #[serde(skip)]
+    pub split_lines: Vec<String>,
+    /// Last key press or IPC command, for `auto_quit_after_idle`.
+    #[serde(skip)]
+    pub last_activity: Option<Instant>,
+}
+
+/// Steps through a fixed number of frames once, then stops.
+#[derive(Clone, Debug)]
+pub struct FrameAnimation {
+    pub frame: usize,
+    last_advance: Instant,
+}
+
+impl FrameAnimation {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            frame: 0,
+            last_advance: now,
+        }
+    }
+
+    /// Moves to the next frame once `interval` has passed. Returns `false`
+    /// when the last of `frame_count` frames has had its turn.
+    pub fn advance(
+        &mut self,
+        now: Instant,
+        frame_count: usize,
+        interval: std::time::Duration,
+    ) -> bool {
+        if now.duration_since(self.last_advance) < interval {
+            return true;
+        }
+        self.last_advance = now;
+        if self.frame + 1 >= frame_count {
+            return false;
+        }
+        self.frame += 1;
+        true
+    }
 }
 
+/// Hints cycled through while the task list is empty.
+const TIPS: &[&str] = &[
+    "Press ? to see every shortcut.",
+    "Press T to set the global timer from a preset.",
+    "Press P to add your own presets, like a 50-minute deep-work block.",
+    "Press e to estimate a task in Pomodoros.",
+    "kronosctl task --add \"...\" adds tasks from another terminal.",
+    "Press v to toggle the compact view.",
+];
+
+/// How long each empty-state tip stays up.
+const TIP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(8);
+
+/// How long each `done_frames` icon is shown.
+const DONE_FRAME_INTERVAL: std::time::Duration = std::time::Duration::from_millis(80);
+
+/// How long a completion toast stays up.
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_millis(2500);
+
+/// How many edits `undo` can go back.
+const UNDO_LIMIT: usize = 50;
+
+/// Minimum gap between two running-timer pulses.
+const RUNNING_PULSE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(1500);
+
 pub fn default_effect_manager() -> EffectManager<u32> {
     EffectManager::default()
 }
 
+/// Parses typed-in timer minutes, capping them at `max`. Above the cap the
+/// value is clamped if `clamp` is set and rejected otherwise.
+pub fn parse_timer_minutes(input: &str, max: i64, clamp: bool) -> Result<i64> {
+    let minutes = match input.parse::<i64>() {
+        Ok(minutes) => minutes,
+        Err(e) if matches!(e.kind(), std::num::IntErrorKind::PosOverflow) => i64::MAX,
+        Err(_) => anyhow::bail!("{:?} is not a number of minutes", input),
+    };
+    cap_timer_minutes(minutes, max, clamp)
+        .map_err(|_| anyhow::anyhow!("{} minutes is over the {} minute limit", input, max))
+}
+
+/// Applies the `max` cap of `parse_timer_minutes` to minutes that are
+/// already a number.
+pub fn cap_timer_minutes(minutes: i64, max: i64, clamp: bool) -> Result<i64> {
+    if minutes > max {
+        if clamp {
+            return Ok(max);
+        }
+        anyhow::bail!("{} minutes is over the {} minute limit", minutes, max);
+    }
+    Ok(minutes)
+}
+
 impl Clone for App {
     fn clone(&self) -> Self {
         Self {
             tasks: self.tasks.clone(),
             selected_task: self.selected_task,
+            selected_task_id: self.selected_task_id,
             next_task_id: self.next_task_id,
             global_timer: self.global_timer.clone(),
             presets: self.presets.clone(),
+            undo_stack: self.undo_stack.clone(),
+            redo_stack: self.redo_stack.clone(),
             mode: self.mode.clone(),
             input_buffer: self.input_buffer.clone(),
             notifications_sent: self.notifications_sent.clone(),
+            notification_failures: self.notification_failures,
+            notifications_unavailable: self.notifications_unavailable,
             config: self.config.clone(),
             effect_manager: EffectManager::default(),
             should_quit: self.should_quit,
+            compact_view: self.compact_view,
+            show_deferred: self.show_deferred,
+            show_only_active: self.show_only_active,
             stats: self.stats.clone(),
             category_list_state: self.category_list_state.clone(),
+            preset_list_state: self.preset_list_state.clone(),
+            command_log: self.command_log.clone(),
+            work_started: self.work_started,
+            last_worked: self.last_worked,
+            break_suggested: self.break_suggested,
+            selection_anchor: self.selection_anchor,
+            last_running_pulse: self.last_running_pulse,
+            status_message: self.status_message.clone(),
+            theme_editor: self.theme_editor.clone(),
+            tip_index: self.tip_index,
+            last_tip_change: self.last_tip_change,
+            events: self.events.clone(),
+            bell_pending: self.bell_pending,
+            done_animations: self.done_animations.clone(),
+            toast: self.toast.clone(),
+            startup_ends_at: self.startup_ends_at,
+            split_lines: self.split_lines.clone(),
+            last_activity: self.last_activity,
         }
     }
 }
@@ -59,12 +247,30 @@ pub enum AppMode {
     #[default]
     Normal,
     AddingTask,
+    /// Adding a task that will be inserted at the given index.
+    InsertingTask(usize),
     EditingTime(usize),
+    EditingEstimate(usize),
+    ShowDetail(usize),
     SelectingPreset(usize),
+    SelectingGlobalPreset,
     SelectingCategory(usize),
+    ManagingPresets,
+    /// Entering a preset name; holds the preset being renamed, if any.
+    EditingPresetName(Option<String>),
+    EditingPresetMinutes(String),
     StartupAnimation,
     ShowStats,
     ShowHelp,
+    EditingTheme,
+    /// Waiting for `y` before merging duplicate tasks.
+    ConfirmingMerge,
+    /// Entering subtasks for the given task, one per line.
+    SplittingTask(usize),
+    /// Entering the given task's due date.
+    EditingDue(usize),
+    /// Editing the given task's description.
+    RenamingTask(usize),
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
@@ -77,18 +283,34 @@ pub enum TaskCategory {
 }
 
 impl TaskCategory {
-    pub fn to_string(&self) -> String {
+    /// Maps a category name to a built-in category, or `Other` for anything
+    /// else.
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "work" => TaskCategory::Work,
+            "personal" => TaskCategory::Personal,
+            "study" => TaskCategory::Study,
+            "exercise" => TaskCategory::Exercise,
+            "" => TaskCategory::Other("General".to_string()),
+            _ => TaskCategory::Other(name.trim().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for TaskCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TaskCategory::Work => "Work".to_string(),
-            TaskCategory::Personal => "Personal".to_string(),
-            TaskCategory::Study => "Study".to_string(),
-            TaskCategory::Exercise => "Exercise".to_string(),
-            TaskCategory::Other(s) => s.clone(),
+            TaskCategory::Work => write!(f, "Work"),
+            TaskCategory::Personal => write!(f, "Personal"),
+            TaskCategory::Study => write!(f, "Study"),
+            TaskCategory::Exercise => write!(f, "Exercise"),
+            TaskCategory::Other(s) => write!(f, "{}", s),
         }
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+/// Ordered from least to most pressing.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Priority {
     Low,
     Medium,
@@ -96,6 +318,31 @@ pub enum Priority {
     Urgent,
 }
 
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            "urgent" => Ok(Priority::Urgent),
+            other => Err(format!("unknown priority '{}'", other)),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::Low => write!(f, "Low"),
+            Priority::Medium => write!(f, "Medium"),
+            Priority::High => write!(f, "High"),
+            Priority::Urgent => write!(f, "Urgent"),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Task {
     pub id: u32,
@@ -106,6 +353,133 @@ pub struct Task {
     pub priority: Priority,
     pub created_at: DateTime<Local>,
     pub completed_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub estimated_pomodoros: u32,
+    /// Hidden from the list until this date.
+    #[serde(default)]
+    pub deferred_until: Option<NaiveDate>,
+    /// Date the task should be done by.
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+    #[serde(default)]
+    pub subtasks: Vec<Subtask>,
+}
+
+/// A checklist item of a task. It has no timer of its own; `target_minutes`
+/// is its share of the task's timer when split with
+/// `split_divides_duration`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Subtask {
+    pub description: String,
+    pub completed: bool,
+    #[serde(default)]
+    pub target_minutes: Option<i64>,
+}
+
+/// A task edit that can be undone. Each holds the value before (`from`)
+/// and after (`to`) the edit; timer runs are not recorded.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Action {
+    /// Description.
+    Rename { id: u32, from: String, to: String },
+    /// Timer length in minutes.
+    Duration { id: u32, from: i64, to: i64 },
+    Category {
+        id: u32,
+        from: TaskCategory,
+        to: TaskCategory,
+    },
+    /// Completion time, `None` while not completed.
+    Completed {
+        id: u32,
+        from: Option<DateTime<Local>>,
+        to: Option<DateTime<Local>>,
+    },
+}
+
+impl Action {
+    /// The action that takes the task back to how it was before this one.
+    pub fn inverse(&self) -> Action {
+        match self.clone() {
+            Action::Rename { id, from, to } => Action::Rename {
+                id,
+                from: to,
+                to: from,
+            },
+            Action::Duration { id, from, to } => Action::Duration {
+                id,
+                from: to,
+                to: from,
+            },
+            Action::Category { id, from, to } => Action::Category {
+                id,
+                from: to,
+                to: from,
+            },
+            Action::Completed { id, from, to } => Action::Completed {
+                id,
+                from: to,
+                to: from,
+            },
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            Action::Rename { .. } => "rename",
+            Action::Duration { .. } => "duration change",
+            Action::Category { .. } => "category change",
+            Action::Completed { to: Some(_), .. } => "completion",
+            Action::Completed { to: None, .. } => "reopening",
+        }
+    }
+}
+
+impl Task {
+    /// Whether the task is still deferred on `date`.
+    pub fn is_deferred_on(&self, date: NaiveDate) -> bool {
+        self.deferred_until.is_some_and(|until| until > date)
+    }
+
+    /// Number of whole Pomodoros worked on this task, using `pomodoro_minutes`
+    /// as the length of a single Pomodoro.
+    pub fn completed_pomodoros(&self, pomodoro_minutes: i64) -> u32 {
+        if pomodoro_minutes <= 0 {
+            return 0;
+        }
+        (self.timer.get_elapsed().num_minutes() / pomodoro_minutes) as u32
+    }
+}
+
+/// A task as described in an import file. Only the description is required.
+#[derive(Deserialize)]
+struct ImportedTask {
+    description: String,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    minutes: Option<i64>,
+    #[serde(default)]
+    estimated_pomodoros: Option<u32>,
+}
+
+/// Cursor of the theme editor overlay.
+#[derive(Clone, Default)]
+pub struct ThemeEditor {
+    /// Index into `Theme::FIELDS`.
+    pub field: usize,
+    /// 0 = red, 1 = green, 2 = blue.
+    pub channel: usize,
+    /// The theme as it was when the editor opened, restored on cancel.
+    pub original: Option<Theme>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -115,8 +489,45 @@ pub struct Stats {
     pub daily_streak: u32,
     pub last_active_date: DateTime<Local>,
     pub tasks_by_category: HashMap<TaskCategory, u32>,
+    /// Task timers run to completion, per category.
+    #[serde(default)]
+    pub timers_completed_by_category: HashMap<TaskCategory, u32>,
+    /// Longest a task timer has run without a pause.
+    #[serde(default)]
+    pub longest_focus_session: Duration,
+    #[serde(default)]
+    pub daily: BTreeMap<NaiveDate, DayRecord>,
+}
+
+/// What happened on a single day.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct DayRecord {
+    pub completed: u32,
+    /// Task timers that ran to completion.
+    #[serde(default)]
+    pub work_sessions: u32,
+    /// Work sessions since the last long break.
+    #[serde(default)]
+    pub sessions_since_long_break: u32,
+    /// Whether reaching `daily_task_goal` has been celebrated.
+    #[serde(default)]
+    pub goal_celebrated: bool,
+    /// Longest uninterrupted timer run that started this day.
+    #[serde(default)]
+    pub longest_focus_session: Duration,
+}
+
+/// How a task's time relates to the average completed task.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AverageComparison {
+    Below,
+    Near,
+    Above,
 }
 
+/// Within this fraction of the average counts as near it.
+const NEAR_AVERAGE_FRACTION: f64 = 0.1;
+
 impl Default for Stats {
     fn default() -> Self {
         Self {
@@ -125,16 +536,259 @@ impl Default for Stats {
             daily_streak: 0,
             last_active_date: Local::now(),
             tasks_by_category: HashMap::new(),
+            timers_completed_by_category: HashMap::new(),
+            longest_focus_session: Duration::zero(),
+            daily: BTreeMap::new(),
+        }
+    }
+}
+
+impl Stats {
+    pub fn record_completion(&mut self, at: DateTime<Local>) {
+        self.daily.entry(at.date_naive()).or_default().completed += 1;
+    }
+
+    pub fn completed_on(&self, date: NaiveDate) -> u32 {
+        self.daily.get(&date).map_or(0, |day| day.completed)
+    }
+
+    pub fn completed_today(&self) -> u32 {
+        self.completed_on(Local::now().date_naive())
+    }
+
+    /// Completed-task and completed-timer counts per category name in a
+    /// stable order. `CategorySort::Count` orders by completed tasks.
+    pub fn sorted_categories(&self, mode: CategorySort) -> Vec<(String, u32, u32)> {
+        let mut counts: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+        for (category, count) in &self.tasks_by_category {
+            counts.entry(category.to_string()).or_default().0 += count;
+        }
+        for (category, count) in &self.timers_completed_by_category {
+            counts.entry(category.to_string()).or_default().1 += count;
+        }
+        let mut rows: Vec<(String, u32, u32)> = counts
+            .into_iter()
+            .map(|(category, (tasks, timers))| (category, tasks, timers))
+            .collect();
+        match mode {
+            CategorySort::Name => rows.sort(),
+            CategorySort::Count => rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0))),
+        }
+        rows
+    }
+
+    /// Keeps `length` as the day's and the all-time longest focus session
+    /// where it beats them.
+    pub fn record_focus_session(&mut self, start: DateTime<Local>, length: Duration) {
+        let day = self.daily.entry(start.date_naive()).or_default();
+        day.longest_focus_session = day.longest_focus_session.max(length);
+        self.longest_focus_session = self.longest_focus_session.max(length);
+    }
+
+    pub fn longest_focus_session_today(&self) -> Duration {
+        self.daily
+            .get(&Local::now().date_naive())
+            .map_or(Duration::zero(), |day| day.longest_focus_session)
+    }
+
+    pub fn record_timer_completion(&mut self, category: &TaskCategory) {
+        *self
+            .timers_completed_by_category
+            .entry(category.clone())
+            .or_insert(0) += 1;
+    }
+
+    /// Mean time worked per completed task, `None` before the first one.
+    pub fn average_time_per_completed(&self) -> Option<Duration> {
+        (self.total_completed > 0).then(|| self.total_time_worked / self.total_completed as i32)
+    }
+
+    /// Places `elapsed` relative to the average, `None` without one.
+    pub fn compare_to_average(&self, elapsed: Duration) -> Option<AverageComparison> {
+        let average = self.average_time_per_completed()?.num_seconds() as f64;
+        let elapsed = elapsed.num_seconds() as f64;
+        let margin = average * NEAR_AVERAGE_FRACTION;
+        Some(if elapsed > average + margin {
+            AverageComparison::Above
+        } else if elapsed < average - margin {
+            AverageComparison::Below
+        } else {
+            AverageComparison::Near
+        })
+    }
+
+    /// Tasks completed today and whether that meets `goal`.
+    pub fn daily_goal_progress(&self, goal: u32) -> (u32, bool) {
+        let done = self.completed_today();
+        (done, done >= goal)
+    }
+
+    /// Marks the goal for `date` as celebrated. Returns `true` only the
+    /// first time, so the celebration fires once per day.
+    pub fn claim_goal_celebration(&mut self, date: NaiveDate) -> bool {
+        let day = self.daily.entry(date).or_default();
+        !std::mem::replace(&mut day.goal_celebrated, true)
+    }
+
+    /// One-line summary for the status bar, e.g. `⌾ 3 today  🔥 5d`.
+    pub fn status_summary(&self, icons: &Icons) -> String {
+        format!(
+            "{} {} today  {} {}d",
+            icons.done,
+            self.completed_today(),
+            icons.streak,
+            self.daily_streak
+        )
+    }
+
+    /// Counts a finished work session and returns how many have happened
+    /// since the last long break today.
+    pub fn record_work_session(&mut self, at: DateTime<Local>) -> u32 {
+        let day = self.daily.entry(at.date_naive()).or_default();
+        day.work_sessions += 1;
+        day.sessions_since_long_break += 1;
+        day.sessions_since_long_break
+    }
+
+    pub fn work_sessions_today(&self) -> u32 {
+        self.daily
+            .get(&Local::now().date_naive())
+            .map_or(0, |day| day.work_sessions)
+    }
+
+    pub fn sessions_since_long_break_today(&self) -> u32 {
+        self.daily
+            .get(&Local::now().date_naive())
+            .map_or(0, |day| day.sessions_since_long_break)
+    }
+
+    pub fn record_long_break(&mut self, at: DateTime<Local>) {
+        if let Some(day) = self.daily.get_mut(&at.date_naive()) {
+            day.sessions_since_long_break = 0;
+        }
+    }
+
+    /// Time spent in task timer sessions that started on `date`. A session
+    /// that's still running counts up to `now`.
+    pub fn worked_on(tasks: &[Task], date: NaiveDate, now: DateTime<Local>) -> Duration {
+        tasks
+            .iter()
+            .flat_map(|task| &task.timer.intervals)
+            .filter(|interval| interval.start.date_naive() == date)
+            .map(|interval| interval.end.unwrap_or(now) - interval.start)
+            .sum()
+    }
+
+    pub fn worked_today(tasks: &[Task]) -> Duration {
+        let now = Local::now();
+        Self::worked_on(tasks, now.date_naive(), now)
+    }
+
+    /// Time worked from the most recent `week_start` through `now`.
+    pub fn worked_this_week(tasks: &[Task], week_start: Weekday, now: DateTime<Local>) -> Duration {
+        let today = now.date_naive();
+        let days_in = today.weekday().days_since(week_start);
+        (0..=days_in)
+            .map(|offset| Self::worked_on(tasks, today - Duration::days(offset.into()), now))
+            .sum()
+    }
+
+    /// This week's worked time as a fraction of `goal_hours`; above 1.0 once
+    /// the goal is exceeded.
+    pub fn weekly_goal_progress(
+        tasks: &[Task],
+        goal_hours: f64,
+        week_start: Weekday,
+        now: DateTime<Local>,
+    ) -> f64 {
+        if goal_hours <= 0.0 {
+            return 0.0;
+        }
+        let worked = Self::worked_this_week(tasks, week_start, now);
+        worked.num_seconds() as f64 / 3600.0 / goal_hours
+    }
+
+    /// Planned minutes left over `date`, as time-ordered samples: one at
+    /// midnight, one after each task added or completed that day, and a
+    /// last one at `now` (or the end of the day, if earlier). Each task
+    /// counts with its timer's target length.
+    pub fn burndown_points(
+        tasks: &[Task],
+        date: NaiveDate,
+        now: DateTime<Local>,
+    ) -> Vec<(DateTime<Local>, i64)> {
+        let local = |hour, min, sec| {
+            date.and_hms_opt(hour, min, sec)
+                .and_then(|t| t.and_local_timezone(Local).earliest())
+        };
+        let (Some(day_start), Some(day_end)) = (local(0, 0, 0), local(23, 59, 59)) else {
+            return Vec::new();
+        };
+
+        let mut remaining = 0;
+        let mut changes = Vec::new();
+        for task in tasks {
+            let minutes = task.timer.target_duration.num_minutes();
+            let completed_at = task.completed_at.filter(|_| task.completed);
+            if task.created_at < day_start {
+                if completed_at.is_none_or(|at| at >= day_start) {
+                    remaining += minutes;
+                }
+            } else if task.created_at <= day_end {
+                changes.push((task.created_at, minutes));
+            }
+            if let Some(at) = completed_at.filter(|at| (day_start..=day_end).contains(at)) {
+                changes.push((at, -minutes));
+            }
         }
+        changes.sort_by_key(|(at, _)| *at);
+
+        let mut points = vec![(day_start, remaining)];
+        for (at, change) in changes {
+            remaining += change;
+            points.push((at, remaining));
+        }
+        if now > day_start {
+            points.push((now.min(day_end), remaining));
+        }
+        points
+    }
+
+    /// Tasks created on `date` minus tasks completed on it; positive means
+    /// the backlog grew. A task created and completed the same day cancels
+    /// out.
+    pub fn net_backlog_change(tasks: &[Task], date: NaiveDate) -> i64 {
+        tasks
+            .iter()
+            .map(|task| {
+                let added = i64::from(task.created_at.date_naive() == date);
+                let completed = i64::from(
+                    task.completed && task.completed_at.is_some_and(|at| at.date_naive() == date),
+                );
+                added - completed
+            })
+            .sum()
     }
 }
 
+/// A span of time a timer spent running. `end` is `None` while it still is.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Interval {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Timer {
     pub state: TimerState,
     pub started_at: Option<DateTime<Local>>,
     pub accumulated_time: Duration,
     pub target_duration: Duration,
+    #[serde(default)]
+    pub intervals: Vec<Interval>,
+    /// Elapsed time at each lap marked since the last reset.
+    #[serde(default)]
+    pub laps: Vec<Duration>,
 }
 
 impl Timer {
@@ -144,49 +798,130 @@ impl Timer {
             started_at: None,
             accumulated_time: Duration::zero(),
             target_duration: Duration::minutes(minutes),
+            intervals: Vec::new(),
+            laps: Vec::new(),
         }
     }
     pub fn toggle(&mut self) {
+        self.toggle_at(Local::now());
+    }
+    /// `toggle` with an explicit clock reading.
+    pub fn toggle_at(&mut self, now: DateTime<Local>) {
         match self.state {
-            TimerState::Idle => {
-                self.state = TimerState::Running;
-                self.started_at = Some(Local::now());
-            }
-            TimerState::Running => {
-                self.state = TimerState::Paused;
-                if let Some(started) = self.started_at {
-                    self.accumulated_time = self.accumulated_time + (Local::now() - started);
-                }
-                self.started_at = None;
-            }
-            TimerState::Paused => {
-                self.state = TimerState::Running;
-                self.started_at = Some(Local::now());
-            }
+            TimerState::Idle | TimerState::Paused => self.start_at(now),
+            TimerState::Running => self.pause_at(now),
+        }
+    }
+    /// Runs the timer from Idle or Paused; a running timer is left alone.
+    pub fn start(&mut self) {
+        self.start_at(Local::now());
+    }
+    pub fn start_at(&mut self, now: DateTime<Local>) {
+        if self.state == TimerState::Running {
+            return;
+        }
+        self.state = TimerState::Running;
+        self.started_at = Some(now);
+        self.intervals.push(Interval {
+            start: now,
+            end: None,
+        });
+    }
+    /// Pauses a running timer; otherwise a no-op.
+    pub fn pause(&mut self) {
+        self.pause_at(Local::now());
+    }
+    pub fn pause_at(&mut self, now: DateTime<Local>) {
+        if self.state != TimerState::Running {
+            return;
+        }
+        self.state = TimerState::Paused;
+        if let Some(started) = self.started_at {
+            self.accumulated_time += now - started;
+        }
+        self.started_at = None;
+        self.close_interval(now);
+    }
+    /// Continues a paused timer; Idle and Running timers are left alone.
+    pub fn resume(&mut self) {
+        self.resume_at(Local::now());
+    }
+    pub fn resume_at(&mut self, now: DateTime<Local>) {
+        if self.state == TimerState::Paused {
+            self.start_at(now);
+        }
+    }
+    /// Returns the timer to Idle but keeps its elapsed time, unlike `reset`.
+    pub fn stop(&mut self) {
+        self.stop_at(Local::now());
+    }
+    pub fn stop_at(&mut self, now: DateTime<Local>) {
+        self.pause_at(now);
+        self.state = TimerState::Idle;
+    }
+    fn close_interval(&mut self, now: DateTime<Local>) {
+        if let Some(open) = self.intervals.last_mut().filter(|i| i.end.is_none()) {
+            open.end = Some(now);
         }
     }
+    /// Zeroes the elapsed time and clears laps. Recorded intervals are kept
+    /// as history.
     pub fn reset(&mut self) {
+        self.close_interval(Local::now());
         self.state = TimerState::Idle;
         self.started_at = None;
         self.accumulated_time = Duration::zero();
+        self.laps.clear();
+    }
+    /// Records the current elapsed time as a lap without stopping the timer.
+    /// Does nothing on a timer that hasn't started.
+    pub fn lap(&mut self) {
+        if self.state != TimerState::Idle {
+            self.laps.push(self.get_elapsed());
+        }
     }
     pub fn get_elapsed(&self) -> Duration {
+        self.get_elapsed_at(Local::now())
+    }
+    pub fn get_elapsed_at(&self, now: DateTime<Local>) -> Duration {
         if let (TimerState::Running, Some(started)) = (self.state.clone(), self.started_at) {
-            self.accumulated_time + (Local::now() - started)
+            self.accumulated_time + (now - started)
         } else {
             self.accumulated_time
         }
     }
+    /// A timer without a positive target just counts up and never completes.
+    pub fn is_open_ended(&self) -> bool {
+        self.target_duration <= Duration::zero()
+    }
     pub fn is_complete(&self) -> bool {
-        self.get_elapsed() >= self.target_duration
+        !self.is_open_ended() && self.get_elapsed() >= self.target_duration
+    }
+    /// Time to show for this timer: what's left, or for an open-ended timer
+    /// the time elapsed so far. The flag is true when it counts up.
+    pub fn shown_time(&self) -> (Duration, bool) {
+        self.shown_time_at(Local::now())
+    }
+    pub fn shown_time_at(&self, now: DateTime<Local>) -> (Duration, bool) {
+        if self.is_open_ended() {
+            (self.get_elapsed_at(now), true)
+        } else {
+            (self.get_remaining_at(now), false)
+        }
     }
     pub fn get_remaining(&self) -> Duration {
+        self.get_remaining_at(Local::now())
+    }
+    pub fn get_remaining_at(&self, now: DateTime<Local>) -> Duration {
         self.target_duration
-            .checked_sub(&self.get_elapsed())
+            .checked_sub(&self.get_elapsed_at(now))
             .unwrap_or_else(Duration::zero)
     }
     pub fn get_progress(&self) -> f64 {
-        let elapsed = self.get_elapsed().num_seconds() as f64;
+        self.get_progress_at(Local::now())
+    }
+    pub fn get_progress_at(&self, now: DateTime<Local>) -> f64 {
+        let elapsed = self.get_elapsed_at(now).num_seconds() as f64;
         let total = self.target_duration.num_seconds() as f64;
         if total > 0.0 {
             (elapsed / total).min(1.0)
@@ -205,24 +940,50 @@ impl App {
         let mut app = Self {
             tasks: vec![],
             selected_task: 0,
+            selected_task_id: None,
             mode: AppMode::StartupAnimation,
             input_buffer: String::new(),
             next_task_id: 1,
             global_timer: Timer::new(25),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
             presets,
             notifications_sent: vec![],
+            notification_failures: 0,
+            notifications_unavailable: false,
             config,
             effect_manager: EffectManager::default(),
             should_quit: false,
+            compact_view: false,
+            show_deferred: false,
+            show_only_active: false,
             stats: Stats::default(),
             category_list_state: ratatui::widgets::ListState::default(),
+            preset_list_state: ratatui::widgets::ListState::default(),
+            command_log: VecDeque::new(),
+            work_started: None,
+            last_worked: None,
+            break_suggested: false,
+            selection_anchor: None,
+            last_running_pulse: None,
+            status_message: None,
+            theme_editor: ThemeEditor::default(),
+            tip_index: 0,
+            last_tip_change: None,
+            events: None,
+            bell_pending: false,
+            done_animations: HashMap::new(),
+            toast: None,
+            startup_ends_at: None,
+            split_lines: Vec::new(),
+            last_activity: None,
         };
         app.trigger_startup_animation();
         app
     }
 
-    pub fn add_task(&mut self, description: String) {
-        self.tasks.push(Task {
+    fn new_task(&mut self, description: String) -> Task {
+        let task = Task {
             id: self.next_task_id,
             description,
             timer: Timer::new(25),
@@ -231,271 +992,3376 @@ impl App {
             priority: Priority::Medium,
             created_at: Local::now(),
             completed_at: None,
-        });
+            estimated_pomodoros: 0,
+            deferred_until: None,
+            due: None,
+            subtasks: Vec::new(),
+        };
         self.next_task_id += 1;
+        task
     }
 
-    pub fn delete_selected_task(&mut self) {
-        if self.tasks.get(self.selected_task).is_some() {
-            let task = self.tasks.remove(self.selected_task);
-            self.notifications_sent.retain(|&id| id != task.id);
-            if !self.tasks.is_empty() && self.selected_task >= self.tasks.len() {
-                self.selected_task = self.tasks.len() - 1;
+    /// Fails once the list has reached the configured `max_tasks`.
+    fn check_task_limit(&self) -> Result<()> {
+        if let Some(max) = self.config.features.max_tasks {
+            if self.tasks.len() >= max {
+                anyhow::bail!("Task limit of {} reached", max);
             }
         }
+        Ok(())
     }
 
-    pub fn toggle_selected_task_completion(&mut self) {
-        let mut task_to_update: Option<Task> = None;
+    pub fn add_task(&mut self, description: String) -> Result<()> {
+        self.add_task_with(description, None, None, None)
+    }
 
-        if let Some(task) = self.tasks.get_mut(self.selected_task) {
-            task.completed = !task.completed;
-            if task.completed {
-                task.completed_at = Some(Local::now());
-                task_to_update = Some(task.clone());
-            } else {
-                task.completed_at = None;
-            }
+    /// Appends a task with the given fields set, leaving the rest at their
+    /// defaults.
+    pub fn add_task_with(
+        &mut self,
+        description: String,
+        category: Option<TaskCategory>,
+        minutes: Option<i64>,
+        priority: Option<Priority>,
+    ) -> Result<()> {
+        self.check_task_limit()?;
+        let mut task = self.new_task(description);
+        if let Some(category) = category {
+            task.category = category;
+        }
+        if let Some(minutes) = minutes {
+            task.timer = Timer::new(minutes);
+        }
+        if let Some(priority) = priority {
+            task.priority = priority;
         }
+        self.tasks.push(task);
+        Ok(())
+    }
+
+    /// Inserts a new task at `index` (clamped to the end of the list) and
+    /// selects it.
+    pub fn add_task_at(&mut self, index: usize, description: String) -> Result<()> {
+        self.check_task_limit()?;
+        let task = self.new_task(description);
+        let index = index.min(self.tasks.len());
+        self.tasks.insert(index, task);
+        self.selected_task = index;
+        Ok(())
+    }
 
-        if let Some(task) = task_to_update {
-            self.update_stats(task);
+    /// Inserts a fresh copy of the selected task right below it and selects
+    /// the copy. Category, priority, duration and estimate carry over; the
+    /// timer and completion state don't.
+    pub fn duplicate_selected_task(&mut self) {
+        if let Err(e) = self.check_task_limit() {
+            self.status_message = Some(e.to_string());
+            return;
+        }
+        let Some(original) = self.tasks.get(self.selected_task) else {
+            return;
+        };
+        let mut description = original.description.clone();
+        if self.config.features.duplicate_suffix {
+            description.push_str(" (copy)");
         }
+        let category = original.category.clone();
+        let priority = original.priority.clone();
+        let target_duration = original.timer.target_duration;
+        let estimated_pomodoros = original.estimated_pomodoros;
+
+        let mut copy = self.new_task(description);
+        copy.category = category;
+        copy.priority = priority;
+        copy.timer.target_duration = target_duration;
+        copy.estimated_pomodoros = estimated_pomodoros;
+        self.tasks.insert(self.selected_task + 1, copy);
+        self.selected_task += 1;
     }
 
-    pub fn update_stats(&mut self, task: Task) {
-        if task.completed {
-            self.stats.total_completed += 1;
-            self.stats.total_time_worked =
-                self.stats.total_time_worked + task.timer.get_elapsed();
+    /// Appends a validated imported task with a fresh id. Returns `false`
+    /// (adding nothing) if any field is invalid.
+    fn import_task(&mut self, imported: ImportedTask) -> bool {
+        if self.check_task_limit().is_err() {
+            return false;
+        }
+        let description = imported.description.trim().to_string();
+        if description.is_empty() {
+            return false;
+        }
+        let priority = match imported.priority.as_deref().map(str::parse::<Priority>) {
+            Some(Ok(priority)) => priority,
+            Some(Err(_)) => return false,
+            None => Priority::Medium,
+        };
+        if imported.minutes.is_some_and(|m| m <= 0) {
+            return false;
+        }
 
-            *self
-                .stats
-                .tasks_by_category
-                .entry(task.category)
-                .or_insert(0) += 1;
+        let mut task = self.new_task(description);
+        task.priority = priority;
+        if let Some(category) = imported.category {
+            task.category = TaskCategory::from_name(&category);
+        }
+        if let Some(minutes) = imported.minutes {
+            task.timer.target_duration = Duration::minutes(minutes);
+        }
+        task.estimated_pomodoros = imported.estimated_pomodoros.unwrap_or(0);
+        self.tasks.push(task);
+        true
+    }
 
-            let today = Local::now().date_naive();
-            let last_active = self.stats.last_active_date.date_naive();
-            if today != last_active {
-                if today.signed_duration_since(last_active).num_days() == 1 {
-                    self.stats.daily_streak += 1;
-                } else {
-                    self.stats.daily_streak = 1;
-                }
-                self.stats.last_active_date = Local::now();
+    /// Imports tasks from a JSON array of objects with a `description` and
+    /// optional `category`, `priority`, `minutes` and `estimated_pomodoros`.
+    pub fn import_from_json(&mut self, json: &str) -> Result<ImportSummary> {
+        let items: Vec<serde_json::Value> =
+            serde_json::from_str(json).context("Expected a JSON array of tasks")?;
+        let mut summary = ImportSummary::default();
+        for item in items {
+            let ok = serde_json::from_value(item).is_ok_and(|task| self.import_task(task));
+            if ok {
+                summary.imported += 1;
+            } else {
+                summary.skipped += 1;
             }
         }
+        Ok(summary)
     }
 
-    pub fn toggle_selected_timer(&mut self) {
-        if let Some(task) = self.tasks.get_mut(self.selected_task) {
-            task.timer.toggle();
+    /// Imports tasks from CSV rows of `description,category,priority,minutes`,
+    /// where every column after the description may be left empty. A header
+    /// row is skipped.
+    pub fn import_from_csv(&mut self, csv: &str) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+        let mut lines = csv.lines().filter(|l| !l.trim().is_empty()).peekable();
+        if let Some(header) = lines.peek() {
+            let first = header.split(',').next().unwrap_or("").trim().to_lowercase();
+            if first == "task" || first == "description" {
+                lines.next();
+            }
+        }
+
+        for line in lines {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let optional = |i: usize| {
+                fields
+                    .get(i)
+                    .filter(|f| !f.is_empty())
+                    .map(|f| f.to_string())
+            };
+            let minutes = match optional(3).map(|m| m.parse::<i64>()) {
+                Some(Ok(minutes)) => Some(minutes),
+                Some(Err(_)) => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                None => None,
+            };
+            let task = ImportedTask {
+                description: fields[0].to_string(),
+                category: optional(1),
+                priority: optional(2),
+                minutes,
+                estimated_pomodoros: None,
+            };
+            if fields.len() <= 4 && self.import_task(task) {
+                summary.imported += 1;
+            } else {
+                summary.skipped += 1;
+            }
         }
+        summary
     }
 
-    pub fn reset_selected_timer(&mut self) {
-        if let Some(task) = self.tasks.get_mut(self.selected_task) {
-            task.timer.reset();
+    pub fn delete_selected_task(&mut self) {
+        if self.tasks.get(self.selected_task).is_some() {
+            let task = self.tasks.remove(self.selected_task);
             self.notifications_sent.retain(|&id| id != task.id);
         }
+        self.clamp_selection();
     }
 
-    pub fn move_selection_up(&mut self) {
-        self.selected_task = self.selected_task.saturating_sub(1);
+    /// Groups task indices by trimmed, case-insensitive description, keeping
+    /// only groups with more than one task. Groups and their members are in
+    /// list order.
+    fn duplicate_groups(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for (idx, task) in self.tasks.iter().enumerate() {
+            let key = task.description.trim().to_lowercase();
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, members)) => members.push(idx),
+                None => groups.push((key, vec![idx])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(_, members)| members)
+            .filter(|members| members.len() > 1)
+            .collect()
     }
 
-    pub fn move_selection_down(&mut self) {
-        if !self.tasks.is_empty() {
-            self.selected_task = (self.selected_task + 1).min(self.tasks.len() - 1);
+    /// Asks for confirmation before `merge_duplicates`, or says there's
+    /// nothing to merge.
+    pub fn request_merge_duplicates(&mut self) {
+        let extra: usize = self.duplicate_groups().iter().map(|g| g.len() - 1).sum();
+        if extra == 0 {
+            self.status_message = Some("No duplicate tasks".to_string());
+            return;
         }
+        self.status_message = Some(format!("Merge away {} duplicate task(s)?", extra));
+        self.mode = AppMode::ConfirmingMerge;
     }
 
-    pub fn set_task_duration(&mut self, task_idx: usize, minutes: i64) {
-        if let Some(task) = self.tasks.get_mut(task_idx) {
-            task.timer.target_duration = Duration::minutes(minutes);
-            task.timer.reset();
+    /// Folds tasks with the same description into the first of them: their
+    /// elapsed time, timer history, laps, subtasks and estimates are added
+    /// to it and it keeps the earliest creation date. A running kept timer
+    /// stays running. Returns how many tasks were removed.
+    pub fn merge_duplicates(&mut self) -> usize {
+        self.anchor_selection();
+        let now = Local::now();
+        let mut removed = vec![];
+        for group in self.duplicate_groups() {
+            let (keep, others) = group.split_first().expect("groups have 2+ members");
+            for &idx in others {
+                self.end_task_run(idx, |timer| timer.pause_at(now));
+                let other = &mut self.tasks[idx];
+                let accumulated = other.timer.accumulated_time;
+                let intervals = std::mem::take(&mut other.timer.intervals);
+                let laps = std::mem::take(&mut other.timer.laps);
+                let subtasks = std::mem::take(&mut other.subtasks);
+                let estimate = other.estimated_pomodoros;
+                let created_at = other.created_at;
+                removed.push(idx);
+
+                let survivor = &mut self.tasks[*keep];
+                survivor.timer.accumulated_time += accumulated;
+                survivor.timer.intervals.extend(intervals);
+                survivor.timer.laps.extend(laps);
+                for subtask in subtasks {
+                    if !survivor
+                        .subtasks
+                        .iter()
+                        .any(|s| s.description == subtask.description)
+                    {
+                        survivor.subtasks.push(subtask);
+                    }
+                }
+                survivor.estimated_pomodoros += estimate;
+                survivor.created_at = survivor.created_at.min(created_at);
+            }
+            // Only the kept timer can still be running; its open interval
+            // has to stay last for the next pause to close it.
+            let intervals = &mut self.tasks[*keep].timer.intervals;
+            let open = intervals
+                .iter()
+                .position(|interval| interval.end.is_none())
+                .map(|i| intervals.remove(i));
+            intervals.sort_by_key(|interval| interval.start);
+            intervals.extend(open);
+        }
+
+        removed.sort_unstable();
+        for &idx in removed.iter().rev() {
+            let task = self.tasks.remove(idx);
             self.notifications_sent.retain(|&id| id != task.id);
         }
+        self.reconcile_selection();
+        removed.len()
     }
 
-    pub fn set_task_duration_from_preset(&mut self, task_idx: usize, preset_name: &str) {
-        if let Some(&minutes) = self.presets.get(preset_name) {
-            self.set_task_duration(task_idx, minutes);
+    pub fn toggle_selected_task_completion(&mut self) {
+        let task_idx = self.selected_task;
+        let Some(task) = self.tasks.get_mut(task_idx) else {
+            // Out of range after an external change: nothing to toggle.
+            self.clamp_selection();
+            return;
+        };
+        if task.completed {
+            let action = Action::Completed {
+                id: task.id,
+                from: task.completed_at,
+                to: None,
+            };
+            task.completed = false;
+            task.completed_at = None;
+            self.record_action(action);
+            return;
+        }
+
+        let timer_finished = task.timer.is_complete();
+        self.complete_task(task_idx);
+        let task = &self.tasks[task_idx];
+        self.record_action(Action::Completed {
+            id: task.id,
+            from: None,
+            to: task.completed_at,
+        });
+        if timer_finished {
+            self.auto_advance_from(task_idx);
         }
     }
 
-    pub fn handle_char(&mut self, c: char) {
-        match self.mode {
-            AppMode::AddingTask => {
-                if c == '\n' {
-                    if !self.input_buffer.is_empty() {
-                        self.add_task(self.input_buffer.clone());
-                    }
-                    self.input_buffer.clear();
-                    self.mode = AppMode::Normal;
+    /// Marks a task done and records it in the worklog and stats. A running
+    /// timer is paused, which records its run as a focus session.
+    fn complete_task(&mut self, task_idx: usize) {
+        self.end_task_run(task_idx, Timer::pause);
+        let Some(task) = self.tasks.get_mut(task_idx) else {
+            return;
+        };
+        task.completed = true;
+        task.completed_at = Some(Local::now());
+        let task = task.clone();
+        if !self.config.icons.done_frames.is_empty() {
+            self.done_animations
+                .insert(task.id, FrameAnimation::new(Instant::now()));
+        }
+        let elapsed = task.timer.get_elapsed();
+        let message = format!(
+            "✓ {} ({}m {:02}s)",
+            task.description,
+            elapsed.num_minutes(),
+            elapsed.num_seconds() % 60
+        );
+        self.toast = Some((message, Instant::now()));
+        let hook = self.config.features.on_complete_command.clone();
+        self.run_task_hook(hook, task.id);
+        if self.config.features.worklog {
+            if let Err(e) = self.append_worklog(&task) {
+                tracing::warn!("Failed to append to worklog: {:#}", e);
+            }
+        }
+        self.update_stats(task);
+    }
+
+    /// With `auto_advance` on, stops the finished task's timer and starts the
+    /// next incomplete task after it. Selection follows only if it was on
+    /// the finished task.
+    fn auto_advance_from(&mut self, task_idx: usize) {
+        if !self.config.features.auto_advance {
+            return;
+        }
+        let len = self.tasks.len();
+        let Some(next_idx) = (1..len)
+            .map(|offset| (task_idx + offset) % len)
+            .find(|&i| !self.tasks[i].completed && !self.tasks[i].timer.is_complete())
+        else {
+            return;
+        };
+
+        self.end_task_run(task_idx, Timer::pause);
+        self.start_task_timer(next_idx);
+        let description = self.tasks[next_idx].description.clone();
+        if self.selected_task == task_idx {
+            self.selected_task = next_idx;
+        }
+        self.send_notification("Next task started", &description);
+    }
+
+    fn worklog_dir(&self) -> Result<PathBuf> {
+        match &self.config.features.worklog_dir {
+            Some(dir) => Ok(PathBuf::from(dir)),
+            None => Ok(Persistence::data_dir()?.join("worklog")),
+        }
+    }
+
+    /// Writes the key binding cheatsheet to `keys.md` in the data dir and
+    /// reports where in the status bar.
+    pub fn export_cheatsheet(&mut self) {
+        let written = Persistence::data_dir().and_then(|dir| {
+            let path = dir.join("keys.md");
+            fs::write(&path, keys::cheatsheet())
+                .with_context(|| format!("Failed to write {:?}", path))?;
+            Ok(path)
+        });
+        self.status_message = Some(match written {
+            Ok(path) => format!("Cheatsheet written to {}", path.display()),
+            Err(e) => format!("{:#}", e),
+        });
+    }
+
+    /// Appends a line for a completed task to that day's worklog file.
+    pub fn append_worklog(&self, task: &Task) -> Result<()> {
+        let completed_at = task.completed_at.unwrap_or_else(Local::now);
+        let dir = self.worklog_dir()?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.log", completed_at.format("%Y-%m-%d")));
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open worklog at {:?}", path))?;
+        writeln!(
+            file,
+            "{}  {}  ({}m)",
+            self.config.features.format_time(completed_at),
+            task.description,
+            task.timer.get_elapsed().num_minutes()
+        )?;
+        file.flush()?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    pub fn update_stats(&mut self, task: Task) {
+        if task.completed {
+            self.stats.total_completed += 1;
+            self.stats.total_time_worked += task.timer.get_elapsed();
+            self.stats
+                .record_completion(task.completed_at.unwrap_or_else(Local::now));
+            self.check_daily_goal();
+
+            *self
+                .stats
+                .tasks_by_category
+                .entry(task.category)
+                .or_insert(0) += 1;
+
+            let today = Local::now().date_naive();
+            let last_active = self.stats.last_active_date.date_naive();
+            if today != last_active {
+                if today.signed_duration_since(last_active).num_days() == 1 {
+                    self.stats.daily_streak += 1;
                 } else {
-                    self.input_buffer.push(c);
+                    self.stats.daily_streak = 1;
                 }
+                self.stats.last_active_date = Local::now();
             }
-            AppMode::EditingTime(task_idx) => {
-                if c == '\n' {
-                    if let Ok(minutes) = self.input_buffer.parse() {
-                        self.set_task_duration(task_idx, minutes);
-                    }
-                    self.input_buffer.clear();
-                    self.mode = AppMode::Normal;
-                } else if c.is_numeric() {
-                    self.input_buffer.push(c);
-                }
+        }
+    }
+
+    /// Celebrates the first time today's completions reach `daily_task_goal`.
+    fn check_daily_goal(&mut self) {
+        let Some(goal) = self.config.features.daily_task_goal else {
+            return;
+        };
+        let (done, reached) = self.stats.daily_goal_progress(goal);
+        if reached && self.stats.claim_goal_celebration(Local::now().date_naive()) {
+            let body = format!("{} tasks done today. Nice work!", done);
+            self.send_notification("🎉 Daily goal reached", &body);
+            self.status_message = Some(format!("🎉 Daily goal reached: {}/{}", done, goal));
+        }
+    }
+
+    pub fn toggle_selected_timer(&mut self) {
+        self.end_task_run(self.selected_task, Timer::toggle);
+        if let Some(task) = self.tasks.get(self.selected_task) {
+            let id = task.id;
+            let event = match task.timer.state {
+                TimerState::Running => TimerEvent::Started { id },
+                _ => TimerEvent::Paused { id },
+            };
+            self.publish(event);
+        }
+    }
+
+    /// Sends `event` to any subscribed IPC clients, and runs the start hook
+    /// when a task timer starts.
+    pub fn publish(&mut self, event: TimerEvent) {
+        if let TimerEvent::Started { id } = event {
+            let hook = self.config.features.on_start_command.clone();
+            self.run_task_hook(hook, id);
+        }
+        if let Some(events) = &self.events {
+            // Nobody listening is fine.
+            let _ = events.send(event);
+        }
+    }
+
+    /// Runs a configured hook for the task with `id`, if both exist. A hook
+    /// that can't be started is reported in the status bar.
+    fn run_task_hook(&mut self, template: Option<String>, id: u32) {
+        let Some(template) = template else {
+            return;
+        };
+        let Some(task) = self.tasks.iter().find(|t| t.id == id) else {
+            return;
+        };
+        if let Err(e) = hooks::run_hook(&template, task) {
+            self.status_message = Some(format!("{:#}", e));
+        }
+    }
+
+    pub fn stop_selected_timer(&mut self) {
+        self.end_task_run(self.selected_task, Timer::stop);
+        if let Some(task) = self.tasks.get(self.selected_task) {
+            let id = task.id;
+            self.publish(TimerEvent::Stopped { id });
+        }
+    }
+
+    pub fn reset_selected_timer(&mut self) {
+        self.end_task_run(self.selected_task, Timer::reset);
+        if let Some(task) = self.tasks.get(self.selected_task) {
+            self.notifications_sent.retain(|&id| id != task.id);
+        }
+    }
+
+    /// Minutes spent on completed tasks per category, sorted by name.
+    /// Categories that only appear in the lifetime stats (their tasks were
+    /// deleted) are listed with zero minutes.
+    pub fn category_breakdown(&self) -> Vec<(String, u64)> {
+        let mut minutes: BTreeMap<String, u64> = self
+            .stats
+            .tasks_by_category
+            .keys()
+            .map(|category| (category.to_string(), 0))
+            .collect();
+        for task in self.tasks.iter().filter(|t| t.completed) {
+            *minutes.entry(task.category.to_string()).or_default() +=
+                task.timer.get_elapsed().num_minutes().max(0) as u64;
+        }
+        minutes.into_iter().collect()
+    }
+
+    /// The empty-state tip to show right now.
+    pub fn current_tip(&self) -> &'static str {
+        TIPS[self.tip_index % TIPS.len()]
+    }
+
+    /// Moves on to the next tip once the current one has been up for
+    /// `TIP_INTERVAL`.
+    pub fn rotate_tip_at(&mut self, now: Instant) {
+        match self.last_tip_change {
+            Some(last) if now.duration_since(last) < TIP_INTERVAL => {}
+            Some(_) => {
+                self.tip_index = (self.tip_index + 1) % TIPS.len();
+                self.last_tip_change = Some(now);
             }
-            AppMode::SelectingPreset(task_idx) => {
-                if c.is_numeric() {
-                    let index = c.to_digit(10).unwrap_or(0) as usize;
-                    if index > 0 && index <= self.presets.len() {
-                        let preset_names = self.get_preset_names();
-                        if let Some(preset_name) = preset_names.get(index - 1) {
-                            self.set_task_duration_from_preset(task_idx, preset_name);
-                            self.mode = AppMode::Normal;
-                        }
-                    }
-                }
+            None => self.last_tip_change = Some(now),
+        }
+    }
+
+    /// The toast's text while it's younger than `TOAST_DURATION`.
+    pub fn active_toast(&self, now: Instant) -> Option<&str> {
+        self.toast
+            .as_ref()
+            .filter(|(_, raised)| now.duration_since(*raised) < TOAST_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Advances completion glyph animations, dropping finished ones.
+    pub fn advance_done_animations(&mut self, now: Instant) {
+        let frame_count = self.config.icons.done_frames.len();
+        self.done_animations
+            .retain(|_, animation| animation.advance(now, frame_count, DONE_FRAME_INTERVAL));
+    }
+
+    /// The icon to show for a completed task: the current animation frame
+    /// while one is playing, else the static `done` icon.
+    pub fn done_icon(&self, task_id: u32) -> &str {
+        let icons = &self.config.icons;
+        self.done_animations
+            .get(&task_id)
+            .and_then(|animation| icons.done_frames.get(animation.frame))
+            .unwrap_or(&icons.done)
+    }
+
+    /// The task whose timer is currently running, if any.
+    pub fn active_timer_task(&self) -> Option<&Task> {
+        self.tasks
+            .iter()
+            .find(|t| t.timer.state == TimerState::Running)
+    }
+
+    /// Terminal title text, e.g. "kronos - Write report 12:34 (45%)".
+    pub fn terminal_title(&self) -> String {
+        self.terminal_title_at(Local::now())
+    }
+
+    /// `terminal_title` with an explicit clock reading.
+    pub fn terminal_title_at(&self, now: DateTime<Local>) -> String {
+        let Some(task) = self.active_timer_task() else {
+            return "kronos".to_string();
+        };
+        let (shown, counting_up) = task.timer.shown_time_at(now);
+        let time = format!("{:02}:{:02}", shown.num_minutes(), shown.num_seconds() % 60);
+        if counting_up {
+            return format!("kronos - {} ↑{}", task.description, time);
+        }
+        format!(
+            "kronos - {} {} ({}%)",
+            task.description,
+            time,
+            (task.timer.get_progress_at(now) * 100.0) as u32
+        )
+    }
+
+    /// Starts the timer of every incomplete task, optionally only those in
+    /// `category`. Returns how many were started; running timers are left
+    /// alone and don't count.
+    pub fn start_all_pending(&mut self, category: Option<&TaskCategory>) -> usize {
+        let pending: Vec<usize> = (0..self.tasks.len())
+            .filter(|&i| {
+                let task = &self.tasks[i];
+                !task.completed && category.is_none_or(|c| *c == task.category)
+            })
+            .collect();
+        pending
+            .into_iter()
+            .filter(|&i| self.start_task_timer(i))
+            .count()
+    }
+
+    /// Starts a task's timer and publishes `TimerEvent::Started`. Returns
+    /// false, publishing nothing, if it was already running or is missing.
+    fn start_task_timer(&mut self, task_idx: usize) -> bool {
+        let Some(task) = self.tasks.get_mut(task_idx) else {
+            return false;
+        };
+        if task.timer.state == TimerState::Running {
+            return false;
+        }
+        task.timer.start();
+        let id = task.id;
+        self.publish(TimerEvent::Started { id });
+        true
+    }
+
+    /// Pushes the selected task's target out by `minutes`, keeping its timer
+    /// running and re-arming its completion notification.
+    pub fn snooze_selected_timer(&mut self, minutes: i64) {
+        if let Some(task) = self.tasks.get_mut(self.selected_task) {
+            task.timer.target_duration += Duration::minutes(minutes);
+            let id = task.id;
+            self.notifications_sent.retain(|&sent| sent != id);
+            self.start_task_timer(self.selected_task);
+        }
+    }
+
+    pub fn open_theme_editor(&mut self) {
+        self.theme_editor = ThemeEditor {
+            field: 0,
+            channel: 0,
+            original: Some(self.config.theme.clone()),
+        };
+        self.mode = AppMode::EditingTheme;
+    }
+
+    pub fn move_theme_field(&mut self, delta: isize) {
+        let editor = &mut self.theme_editor;
+        editor.field = editor
+            .field
+            .saturating_add_signed(delta)
+            .min(Theme::FIELDS.len() - 1);
+    }
+
+    /// Nudges the selected channel of the selected theme color; the change
+    /// shows up on the next frame.
+    pub fn nudge_theme_color(&mut self, delta: i16) {
+        let editor = &self.theme_editor;
+        if let Some(color) = self.config.theme.color_mut(editor.field) {
+            *color = config::nudge_channel(*color, editor.channel, delta);
+        }
+    }
+
+    /// Writes the edited theme to the config file and closes the editor.
+    pub fn save_theme(&mut self) {
+        self.status_message = Some(match config::save_theme(&self.config.theme) {
+            Ok(path) => format!("Theme saved to {}", path.display()),
+            Err(e) => format!("Failed to save theme: {:#}", e),
+        });
+        self.theme_editor.original = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Re-reads `kronos.toml`, keeping tasks and timers. A config that fails
+    /// to load leaves the current one in place.
+    pub fn reload_config(&mut self) {
+        self.status_message = Some(match config::load_config() {
+            Ok(config) => {
+                self.config = config;
+                "Config reloaded".to_string()
             }
-            _ => {}
+            Err(e) => format!("Failed to reload config: {:#}", e),
+        });
+    }
+
+    /// Flips between MM:SS and MM timer displays and writes the choice to
+    /// the config file. If that fails the change still holds for the session.
+    pub fn toggle_show_seconds(&mut self) {
+        let value = toml::Value::Boolean(self.config.features.toggle_show_seconds());
+        if let Err(e) = config::save_feature("show_seconds", value) {
+            self.status_message = Some(format!("Failed to save show_seconds: {:#}", e));
         }
     }
 
-    pub fn handle_backspace(&mut self) {
-        if matches!(self.mode, AppMode::AddingTask | AppMode::EditingTime(_)) {
-            self.input_buffer.pop();
+    /// Whether animations play: `effects_enabled` if it is set, otherwise
+    /// unless the environment asks for reduced motion.
+    pub fn effects_active(&self) -> bool {
+        config::effects_active_from(
+            self.config.features.effects_enabled,
+            config::reduced_motion_requested(),
+        )
+    }
+
+    /// Turns animations on or off and writes the choice to the config file,
+    /// where it then overrides any reduced-motion setting.
+    pub fn toggle_effects(&mut self) {
+        let enabled = !self.effects_active();
+        self.config.features.effects_enabled = Some(enabled);
+        if !enabled {
+            self.effect_manager = EffectManager::default();
+        }
+        if let Err(e) = config::save_feature("effects_enabled", toml::Value::Boolean(enabled)) {
+            self.status_message = Some(format!("Failed to save effects_enabled: {:#}", e));
         }
     }
 
-    pub fn check_and_notify_completions(&mut self) {
-        if self.global_timer.is_complete()
-            && self.global_timer.state == TimerState::Running
-            && !self.notifications_sent.contains(&0)
-        {
-            self.send_notification("Global Timer", "Timer completed!");
-            self.notifications_sent.push(0);
+    /// Throws away unsaved edits and closes the editor.
+    pub fn cancel_theme_editor(&mut self) {
+        if let Some(original) = self.theme_editor.original.take() {
+            self.config.theme = original;
         }
-        for task in &self.tasks {
-            if task.timer.is_complete()
-                && task.timer.state == TimerState::Running
-                && !self.notifications_sent.contains(&task.id)
-            {
-                self.send_notification(&task.description, "Task timer completed!");
-                self.notifications_sent.push(task.id);
+        self.mode = AppMode::Normal;
+    }
+
+    /// Selects the task with `id`. Returns `false` if there's no such task.
+    pub fn select_task_by_id(&mut self, id: u32) -> bool {
+        match self.tasks.iter().position(|t| t.id == id) {
+            Some(idx) => {
+                self.selected_task = idx;
+                true
             }
+            None => false,
         }
     }
 
-    fn send_notification(&self, title: &str, body: &str) {
-        if self.config.features.notification_sound {
-            if let Err(e) = notify_rust::Notification::new()
-                .summary(title)
-                .body(body)
-                .appname("kronos")
-                .show()
-            {
-                eprintln!("Failed to send notification: {}", e);
+    /// The task to work on next: the highest-priority incomplete task, then
+    /// the one due soonest (undated tasks last), then the oldest. Tasks
+    /// deferred past today are left out. `None` when everything is done.
+    pub fn suggest_next_task(&self) -> Option<usize> {
+        let today = Local::now().date_naive();
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| !task.completed && !task.is_deferred_on(today))
+            .min_by(|(_, a), (_, b)| {
+                b.priority
+                    .cmp(&a.priority)
+                    .then(a.due.is_none().cmp(&b.due.is_none()))
+                    .then(a.due.cmp(&b.due))
+                    .then(a.created_at.cmp(&b.created_at))
+            })
+            .map(|(idx, _)| idx)
+    }
+
+    /// Selects the suggested task, or says there is nothing left.
+    pub fn select_suggested_task(&mut self) {
+        match self.suggest_next_task() {
+            Some(idx) => {
+                self.selected_task = idx;
+                self.status_message = Some(format!("Suggested: {}", self.tasks[idx].description));
             }
+            None => self.status_message = Some("Nothing left to work on".to_string()),
         }
     }
 
-    pub fn get_preset_names(&self) -> Vec<String> {
-        let mut names: Vec<_> = self.presets.keys().cloned().collect();
-        names.sort();
-        names
+    /// Remembers the selected task's id so the selection can follow it if
+    /// the list changes underneath (see `reconcile_selection`).
+    pub fn anchor_selection(&mut self) {
+        self.selection_anchor = self.tasks.get(self.selected_task).map(|t| t.id);
     }
 
-    pub fn get_category_names(&self) -> Vec<String> {
-        [
-            "Work",
-            "Personal",
-            "Study",
-            "Exercise",
-            "General",
-        ]
-        .iter()
-        .map(|s| s.to_string())
-        .collect()
+    /// Moves the selection back onto the anchored task after external
+    /// mutations, or clamps it into range if that task is gone.
+    pub fn reconcile_selection(&mut self) {
+        if let Some(id) = self.selection_anchor.take() {
+            if let Some(idx) = self.tasks.iter().position(|t| t.id == id) {
+                self.selected_task = idx;
+            }
+        }
+        self.clamp_selection();
+        // A deferral can lapse or start while the selection sits still.
+        self.move_off_hidden_task();
+        self.selected_task_id = self.tasks.get(self.selected_task).map(|t| t.id);
     }
 
-    pub fn set_task_category(&mut self, task_idx: usize, category: TaskCategory) {
-        if let Some(task) = self.tasks.get_mut(task_idx) {
-            task.category = category;
+    /// Pulls `selected_task` back into range, or to 0 for an empty list.
+    /// Safe to call at any time.
+    pub fn clamp_selection(&mut self) {
+        self.selected_task = self.selected_task.min(self.tasks.len().saturating_sub(1));
+    }
+
+    /// Restores what every command must leave true: new ids can't collide
+    /// with existing ones, and the selection is in range.
+    pub fn enforce_invariants(&mut self) {
+        let max_id = self.tasks.iter().map(|t| t.id).max().unwrap_or(0);
+        self.next_task_id = self.next_task_id.max(max_id + 1);
+        self.clamp_selection();
+    }
+
+    /// Points the selection at the saved `selected_task_id` after loading,
+    /// falling back to the first task if it's gone. State saved before ids
+    /// were kept just has its index clamped.
+    pub fn restore_selection_by_id(&mut self) {
+        match self.selected_task_id {
+            Some(id) => {
+                if !self.select_task_by_id(id) {
+                    self.selected_task = 0;
+                }
+            }
+            None => {
+                self.clamp_selection();
+            }
         }
     }
 
-    pub fn trigger_startup_animation(&mut self) {
-        self.effect_manager.add_effect(fx::sweep_in(
-            Motion::UpToDown,
-            20,
-            0,
-            self.config.theme.selection,
-            800,
-        ));
+    /// Whether the task at `idx` is shown in the list: deferred tasks are
+    /// hidden until their date unless `show_deferred` is on, and with
+    /// `show_only_active` only active timers are listed.
+    pub fn is_task_visible(&self, idx: usize) -> bool {
+        let today = Local::now().date_naive();
+        self.tasks.get(idx).is_some_and(|task| {
+            (self.show_deferred || !task.is_deferred_on(today))
+                && (!self.show_only_active || self.is_timer_active(&task.timer))
+        })
     }
 
-    pub fn trigger_mode_change_effect(&mut self, area: Rect) {
-        let effect = fx::slide_in(Motion::LeftToRight, 8, 4, self.config.theme.selection, 300)
-            .with_area(area);
-        self.effect_manager.add_effect(effect);
+    /// Running, or also paused with `active_includes_paused`.
+    fn is_timer_active(&self, timer: &Timer) -> bool {
+        match timer.state {
+            TimerState::Running => true,
+            TimerState::Paused => self.config.features.active_includes_paused,
+            TimerState::Idle => false,
+        }
     }
 
-    pub fn trigger_delete_effect(&mut self, area: Rect) {
-        let effect = fx::dissolve(500).with_area(area);
-        self.effect_manager.add_effect(effect);
+    pub fn note_activity(&mut self, now: Instant) {
+        self.last_activity = Some(now);
     }
 
-    pub fn trigger_complete_effect(&mut self, area: Rect) {
-        let effect = fx::dissolve(250).with_area(area);
-        self.effect_manager.add_effect(effect);
+    /// Whether `auto_quit_after_idle` has run out: nothing has happened for
+    /// that long and no timer is running. Idle time counts from the first
+    /// call when there has been no activity yet.
+    pub fn idle_quit_due(&mut self, now: Instant) -> bool {
+        let Some(minutes) = self.config.features.auto_quit_after_idle else {
+            return false;
+        };
+        let running = self.global_timer.state == TimerState::Running
+            || self
+                .tasks
+                .iter()
+                .any(|t| t.timer.state == TimerState::Running);
+        if running {
+            // A timer ending doesn't start the idle clock from long ago.
+            self.note_activity(now);
+            return false;
+        }
+        let last = *self.last_activity.get_or_insert(now);
+        now.duration_since(last) >= std::time::Duration::from_secs(minutes * 60)
     }
 
-    pub fn trigger_task_complete_celebration(&mut self, area: Rect) {
-        self.effect_manager
-            .add_effect(fx::fade_to_fg(self.config.theme.green, 500).with_area(area));
+    /// Indices of tasks whose timers are active, in list order.
+    pub fn active_task_indices(&self) -> Vec<usize> {
+        (0..self.tasks.len())
+            .filter(|&i| self.is_timer_active(&self.tasks[i].timer))
+            .collect()
     }
 
-    pub fn trigger_streak_animation(&mut self, area: Rect) {
-        self.effect_manager
-            .add_effect(fx::fade_to_fg(self.config.theme.magenta, 2000).with_area(area));
+    pub fn toggle_show_only_active(&mut self) {
+        self.show_only_active = !self.show_only_active;
+        self.move_off_hidden_task();
     }
 
-    pub fn show_stats_summary(&self) -> String {
-        format!(
-            "📊 Total: {} tasks | ⏱️  {} hours | 🔥 {} day streak",
-            self.stats.total_completed,
-            self.stats.total_time_worked.num_hours(),
-            self.stats.daily_streak
-        )
+    /// Defers the selected task by `days` from today, hiding it until then.
+    /// Zero days brings a deferred task back.
+    pub fn defer_selected_task(&mut self, days: i64) {
+        let Some(task) = self.tasks.get_mut(self.selected_task) else {
+            return;
+        };
+        task.deferred_until = if days > 0 {
+            Some(Local::now().date_naive() + Duration::days(days))
+        } else {
+            None
+        };
+        self.move_off_hidden_task();
     }
 
-    pub fn export_to_csv(&self) -> Result<String, std::fmt::Error> {
-        let mut csv =
-            String::from("Task,Category,Priority,Time Spent,Completed,Created,Completed At\n");
-        for task in &self.tasks {
-            let category = task.category.to_string();
-            let priority = match task.priority {
-                Priority::Low => "Low",
-                Priority::Medium => "Medium",
-                Priority::High => "High",
-                Priority::Urgent => "Urgent",
-            };
-            csv.push_str(&format!(
-                "{},{},{},{},{},{},{}\n",
-                task.description,
-                category,
-                priority,
-                task.timer.get_elapsed().num_minutes(),
-                task.completed,
-                task.created_at.format("%Y-%m-%d %H:%M"),
-                task.completed_at
-                    .map_or("N/A".to_string(), |d| d.format("%Y-%m-%d %H:%M").to_string())
-            ));
+    pub fn toggle_show_deferred(&mut self) {
+        self.show_deferred = !self.show_deferred;
+        self.move_off_hidden_task();
+    }
+
+    /// Moves the selection to the nearest visible task, preferring the ones
+    /// below, if the selected one is hidden.
+    fn move_off_hidden_task(&mut self) {
+        if self.is_task_visible(self.selected_task) {
+            return;
         }
-        Ok(csv)
+        let below = (self.selected_task + 1..self.tasks.len()).find(|&i| self.is_task_visible(i));
+        let above = (0..self.selected_task)
+            .rev()
+            .find(|&i| self.is_task_visible(i));
+        if let Some(idx) = below.or(above) {
+            self.selected_task = idx;
+        }
+    }
+
+    pub fn move_selection_up(&mut self) {
+        if let Some(idx) = (0..self.selected_task)
+            .rev()
+            .find(|&i| self.is_task_visible(i))
+        {
+            self.selected_task = idx;
+        }
+    }
+
+    pub fn move_selection_down(&mut self) {
+        let mut below = self.selected_task + 1..self.tasks.len();
+        if let Some(idx) = below.find(|&i| self.is_task_visible(i)) {
+            self.selected_task = idx;
+        }
+    }
+
+    /// Moves the selection to the next incomplete task after the current
+    /// one, wrapping around. Does nothing if every task is complete.
+    pub fn select_next_incomplete(&mut self) {
+        let len = self.tasks.len();
+        if let Some(idx) = (1..=len)
+            .map(|offset| (self.selected_task + offset) % len)
+            .find(|&i| !self.tasks[i].completed && self.is_task_visible(i))
+        {
+            self.selected_task = idx;
+        }
+    }
+
+    /// Moves the selection to the previous incomplete task, wrapping around.
+    /// Does nothing if every task is complete.
+    pub fn select_prev_incomplete(&mut self) {
+        let len = self.tasks.len();
+        if let Some(idx) = (1..=len)
+            .map(|offset| (self.selected_task + len - offset) % len)
+            .find(|&i| !self.tasks[i].completed && self.is_task_visible(i))
+        {
+            self.selected_task = idx;
+        }
+    }
+
+    pub fn set_task_duration(&mut self, task_idx: usize, minutes: i64) {
+        self.end_task_run(task_idx, Timer::reset);
+        if let Some(task) = self.tasks.get_mut(task_idx) {
+            let from = task.timer.target_duration.num_minutes();
+            task.timer.target_duration = Duration::minutes(minutes);
+            let id = task.id;
+            self.notifications_sent.retain(|&sent| sent != id);
+            self.record_duration_change(id, from, minutes);
+        }
+    }
+
+    /// Changes a task's target without touching its elapsed time or state,
+    /// so work already done counts toward the new duration.
+    pub fn set_task_duration_keep_elapsed(&mut self, task_idx: usize, minutes: i64) {
+        if let Some(task) = self.tasks.get_mut(task_idx) {
+            let from = task.timer.target_duration.num_minutes();
+            task.timer.target_duration = Duration::minutes(minutes);
+            let id = task.id;
+            self.notifications_sent.retain(|&sent| sent != id);
+            self.record_duration_change(id, from, minutes);
+        }
+    }
+
+    fn record_duration_change(&mut self, id: u32, from: i64, to: i64) {
+        if from != to {
+            self.record_action(Action::Duration { id, from, to });
+        }
+    }
+
+    /// Remembers an edit for `undo`, dropping the oldest past `UNDO_LIMIT`.
+    /// A new edit makes anything undone final.
+    fn record_action(&mut self, action: Action) {
+        self.undo_stack.push(action);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Sets the task the action names to the action's `to` value, without
+    /// recording it. Returns false if that task is gone.
+    fn apply_action(&mut self, action: &Action) -> bool {
+        let id = match action {
+            Action::Rename { id, .. }
+            | Action::Duration { id, .. }
+            | Action::Category { id, .. }
+            | Action::Completed { id, .. } => *id,
+        };
+        let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) else {
+            return false;
+        };
+        match action {
+            Action::Rename { to, .. } => task.description = to.clone(),
+            Action::Duration { to, .. } => {
+                task.timer.target_duration = Duration::minutes(*to);
+                self.notifications_sent.retain(|&sent| sent != id);
+            }
+            Action::Category { to, .. } => task.category = to.clone(),
+            Action::Completed { to, .. } => {
+                task.completed = to.is_some();
+                task.completed_at = *to;
+            }
+        }
+        true
+    }
+
+    /// Reverses `action`. A reverted duration keeps the elapsed time, and
+    /// reopening a task leaves its stats alone.
+    pub fn apply_inverse(&mut self, action: &Action) -> bool {
+        self.apply_action(&action.inverse())
+    }
+
+    /// Reverses the latest edit and keeps it for `redo`.
+    pub fn undo(&mut self) {
+        let Some(action) = self.undo_stack.pop() else {
+            self.status_message = Some("Nothing to undo".to_string());
+            return;
+        };
+        self.status_message = Some(if self.apply_inverse(&action) {
+            let message = format!("Undid {}", action.describe());
+            self.redo_stack.push(action);
+            message
+        } else {
+            "Can't undo: the task is gone".to_string()
+        });
+    }
+
+    /// Applies the latest undone edit again.
+    pub fn redo(&mut self) {
+        let Some(action) = self.redo_stack.pop() else {
+            self.status_message = Some("Nothing to redo".to_string());
+            return;
+        };
+        self.status_message = Some(if self.apply_action(&action) {
+            let message = format!("Redid {}", action.describe());
+            self.undo_stack.push(action);
+            message
+        } else {
+            "Can't redo: the task is gone".to_string()
+        });
+    }
+
+    /// Sets the global timer's target from a preset and resets it.
+    pub fn set_global_duration_from_preset(&mut self, preset_name: &str) {
+        if let Some(&minutes) = self.presets.get(preset_name) {
+            self.global_timer.target_duration = Duration::minutes(minutes);
+            self.global_timer.reset();
+            self.notifications_sent.retain(|&id| id != 0);
+        }
+    }
+
+    pub fn set_task_estimate(&mut self, task_idx: usize, pomodoros: u32) {
+        if let Some(task) = self.tasks.get_mut(task_idx) {
+            task.estimated_pomodoros = pomodoros;
+        }
+    }
+
+    /// Length of a Pomodoro in minutes, taken from the `Pomodoro` preset.
+    pub fn pomodoro_minutes(&self) -> i64 {
+        self.presets.get("Pomodoro").copied().unwrap_or(25)
+    }
+
+    /// Time left on the timers of incomplete tasks, or only running ones
+    /// with `remaining_running_only`. Overrun timers count as zero.
+    pub fn total_remaining(&self) -> Duration {
+        let running_only = self.config.features.remaining_running_only;
+        self.tasks
+            .iter()
+            .filter(|t| !t.completed)
+            .filter(|t| !running_only || t.timer.state == TimerState::Running)
+            .map(|t| t.timer.get_remaining().max(Duration::zero()))
+            .sum()
+    }
+
+    /// Completed vs planned Pomodoros summed over all tasks.
+    pub fn pomodoro_totals(&self) -> (u32, u32) {
+        let pomodoro_minutes = self.pomodoro_minutes();
+        self.tasks.iter().fold((0, 0), |(done, planned), task| {
+            (
+                done + task.completed_pomodoros(pomodoro_minutes),
+                planned + task.estimated_pomodoros,
+            )
+        })
+    }
+
+    pub fn set_task_duration_from_preset(&mut self, task_idx: usize, preset_name: &str) {
+        if let Some(&minutes) = self.presets.get(preset_name) {
+            if self.config.features.keep_elapsed_on_preset {
+                self.set_task_duration_keep_elapsed(task_idx, minutes);
+            } else {
+                self.set_task_duration(task_idx, minutes);
+            }
+        }
+    }
+
+    /// Adds a new preset, rejecting empty or duplicate names and zero durations.
+    pub fn add_preset(&mut self, name: &str, minutes: i64) -> bool {
+        let name = name.trim();
+        if name.is_empty() || minutes <= 0 || self.presets.contains_key(name) {
+            return false;
+        }
+        self.presets.insert(name.to_string(), minutes);
+        true
+    }
+
+    pub fn remove_preset(&mut self, name: &str) -> bool {
+        self.presets.remove(name).is_some()
+    }
+
+    pub fn rename_preset(&mut self, old_name: &str, new_name: &str) -> bool {
+        let new_name = new_name.trim();
+        if new_name.is_empty() || self.presets.contains_key(new_name) {
+            return false;
+        }
+        match self.presets.remove(old_name) {
+            Some(minutes) => {
+                self.presets.insert(new_name.to_string(), minutes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_preset_minutes(&mut self, name: &str, minutes: i64) -> bool {
+        match self.presets.get_mut(name) {
+            Some(value) if minutes > 0 => {
+                *value = minutes;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn selected_preset_name(&self) -> Option<String> {
+        self.preset_list_state
+            .selected()
+            .and_then(|i| self.get_preset_names().get(i).cloned())
+    }
+
+    pub fn delete_selected_preset(&mut self) {
+        if let Some(name) = self.selected_preset_name() {
+            self.remove_preset(&name);
+            let remaining = self.presets.len();
+            if remaining == 0 {
+                self.preset_list_state.select(None);
+            } else if let Some(selected) = self.preset_list_state.selected() {
+                self.preset_list_state
+                    .select(Some(selected.min(remaining - 1)));
+            }
+        }
+    }
+
+    pub fn handle_char(&mut self, c: char) {
+        match self.mode {
+            AppMode::AddingTask => {
+                if c == '\n' {
+                    let entered = !self.input_buffer.is_empty();
+                    if entered {
+                        if let Err(e) = self.add_task(self.input_buffer.clone()) {
+                            self.status_message = Some(e.to_string());
+                        }
+                    }
+                    self.input_buffer.clear();
+                    if !(entered && self.config.features.sticky_add) {
+                        self.mode = AppMode::Normal;
+                    }
+                } else {
+                    self.input_buffer.push(c);
+                }
+            }
+            AppMode::InsertingTask(index) => {
+                if c == '\n' {
+                    if !self.input_buffer.is_empty() {
+                        if let Err(e) = self.add_task_at(index, self.input_buffer.clone()) {
+                            self.status_message = Some(e.to_string());
+                        }
+                    }
+                    self.input_buffer.clear();
+                    self.mode = AppMode::Normal;
+                } else {
+                    self.input_buffer.push(c);
+                }
+            }
+            AppMode::EditingTime(task_idx) => {
+                if c == '\n' {
+                    let features = &self.config.features;
+                    if !self.input_buffer.is_empty() {
+                        match parse_timer_minutes(
+                            &self.input_buffer,
+                            features.max_timer_minutes,
+                            features.clamp_timer_minutes,
+                        ) {
+                            Ok(minutes) => self.set_task_duration(task_idx, minutes),
+                            Err(e) => self.status_message = Some(e.to_string()),
+                        }
+                    }
+                    self.input_buffer.clear();
+                    self.mode = AppMode::Normal;
+                } else if c.is_numeric() {
+                    self.input_buffer.push(c);
+                }
+            }
+            AppMode::SplittingTask(task_idx) => {
+                if c != '\n' {
+                    self.input_buffer.push(c);
+                } else if !self.input_buffer.trim().is_empty() {
+                    self.split_lines
+                        .push(std::mem::take(&mut self.input_buffer));
+                } else {
+                    // An empty line ends the list.
+                    let lines = std::mem::take(&mut self.split_lines);
+                    let divide = self.config.features.split_divides_duration;
+                    let added = self.split_task(task_idx, lines, divide);
+                    self.status_message = Some(format!("Added {} subtask(s)", added));
+                    self.input_buffer.clear();
+                    self.mode = AppMode::Normal;
+                }
+            }
+            AppMode::EditingDue(task_idx) => {
+                if c == '\n' {
+                    let input = std::mem::take(&mut self.input_buffer);
+                    if let Err(e) = self.set_task_due(task_idx, &input) {
+                        self.status_message = Some(format!("{:#}", e));
+                    }
+                    self.mode = AppMode::Normal;
+                } else {
+                    self.input_buffer.push(c);
+                }
+            }
+            AppMode::RenamingTask(task_idx) => {
+                if c == '\n' {
+                    let description = std::mem::take(&mut self.input_buffer);
+                    self.rename_task(task_idx, &description);
+                    self.mode = AppMode::Normal;
+                } else {
+                    self.input_buffer.push(c);
+                }
+            }
+            AppMode::EditingEstimate(task_idx) => {
+                if c == '\n' {
+                    if let Ok(pomodoros) = self.input_buffer.parse() {
+                        self.set_task_estimate(task_idx, pomodoros);
+                    }
+                    self.input_buffer.clear();
+                    self.mode = AppMode::Normal;
+                } else if c.is_numeric() {
+                    self.input_buffer.push(c);
+                }
+            }
+            AppMode::SelectingPreset(_) | AppMode::SelectingGlobalPreset => {
+                // Digits pick from the filtered list, Enter takes the best
+                // match and anything else narrows the query.
+                let matches = self.filtered_preset_names();
+                let choice = match c {
+                    '\n' => matches.first(),
+                    _ if c.is_ascii_digit() => {
+                        let index = c.to_digit(10).unwrap_or(0) as usize;
+                        matches.get(index.wrapping_sub(1))
+                    }
+                    _ => {
+                        self.input_buffer.push(c);
+                        return;
+                    }
+                };
+                let Some(preset_name) = choice.cloned() else {
+                    return;
+                };
+                match self.mode {
+                    AppMode::SelectingPreset(task_idx) => {
+                        self.set_task_duration_from_preset(task_idx, &preset_name)
+                    }
+                    _ => self.set_global_duration_from_preset(&preset_name),
+                }
+                self.input_buffer.clear();
+                self.mode = AppMode::Normal;
+            }
+            AppMode::SelectingCategory(_) => {
+                self.input_buffer.push(c);
+                self.category_list_state.select(Some(0));
+            }
+            AppMode::EditingPresetName(ref original) => {
+                if c == '\n' {
+                    let name = self.input_buffer.trim().to_string();
+                    let accepted = match original {
+                        _ if name.is_empty() => true,
+                        Some(old_name) => {
+                            let old_name = old_name.clone();
+                            old_name == name || self.rename_preset(&old_name, &name)
+                        }
+                        None if self.presets.contains_key(&name) => false,
+                        None => {
+                            self.mode = AppMode::EditingPresetMinutes(name);
+                            self.input_buffer.clear();
+                            return;
+                        }
+                    };
+                    // Rejected names stay in the buffer so they can be corrected.
+                    if accepted {
+                        self.input_buffer.clear();
+                        self.mode = AppMode::ManagingPresets;
+                    }
+                } else {
+                    self.input_buffer.push(c);
+                }
+            }
+            AppMode::EditingPresetMinutes(ref name) => {
+                if c == '\n' {
+                    let name = name.clone();
+                    let minutes: i64 = self.input_buffer.parse().unwrap_or(0);
+                    let accepted = if self.presets.contains_key(&name) {
+                        self.set_preset_minutes(&name, minutes)
+                    } else {
+                        self.add_preset(&name, minutes)
+                    };
+                    if accepted {
+                        self.input_buffer.clear();
+                        self.mode = AppMode::ManagingPresets;
+                    }
+                } else if c.is_numeric() {
+                    self.input_buffer.push(c);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn handle_backspace(&mut self) {
+        if matches!(
+            self.mode,
+            AppMode::AddingTask
+                | AppMode::InsertingTask(_)
+                | AppMode::EditingTime(_)
+                | AppMode::EditingEstimate(_)
+                | AppMode::EditingPresetName(_)
+                | AppMode::EditingPresetMinutes(_)
+                | AppMode::SelectingPreset(_)
+                | AppMode::SelectingGlobalPreset
+                | AppMode::SelectingCategory(_)
+                | AppMode::SplittingTask(_)
+                | AppMode::RenamingTask(_)
+                | AppMode::EditingDue(_)
+        ) {
+            self.input_buffer.pop();
+            self.category_list_state.select(Some(0));
+        }
+    }
+
+    pub fn check_and_notify_completions(&mut self) {
+        if self.global_timer.is_complete()
+            && self.global_timer.state == TimerState::Running
+            && !self.notifications_sent.contains(&0)
+        {
+            if self.config.features.global_alarm {
+                // One-shot countdown: stop at zero instead of running into overrun.
+                self.global_timer.pause();
+                self.send_notification("Global Alarm", "Time's up!");
+            } else {
+                self.send_notification("Global Timer", "Timer completed!");
+            }
+            self.ring_bell();
+            self.notifications_sent.push(0);
+            self.publish(TimerEvent::Completed { id: 0 });
+        }
+        let mut finished = vec![];
+        for (i, task) in self.tasks.iter().enumerate() {
+            if task.timer.is_complete()
+                && task.timer.state == TimerState::Running
+                && !self.notifications_sent.contains(&task.id)
+            {
+                finished.push((i, task.id, task.description.clone()));
+            }
+        }
+        let now = Local::now();
+        for (task_idx, id, description) in finished {
+            self.send_notification(&description, "Task timer completed!");
+            self.ring_bell();
+            self.notifications_sent.push(id);
+            self.publish(TimerEvent::Completed { id });
+            self.stats
+                .record_timer_completion(&self.tasks[task_idx].category);
+            // Finished timers are only seen once thanks to notifications_sent,
+            // so this can't complete a task twice.
+            if self.config.features.complete_on_timer_finish && !self.tasks[task_idx].completed {
+                self.complete_task(task_idx);
+            }
+            self.auto_advance_from(task_idx);
+            self.record_work_session_at(now);
+        }
+        self.track_work_at(now);
+    }
+
+    /// Applies `transition` to a task's timer and, if that ended a run,
+    /// feeds the run into the focus session records. Runs are counted when
+    /// they end, on a pause, stop, reset or completion.
+    fn end_task_run(&mut self, task_idx: usize, transition: impl FnOnce(&mut Timer)) {
+        let Some(task) = self.tasks.get_mut(task_idx) else {
+            return;
+        };
+        let was_running = task.timer.state == TimerState::Running;
+        transition(&mut task.timer);
+        if !was_running || task.timer.state == TimerState::Running {
+            return;
+        }
+        if let Some(&Interval {
+            start,
+            end: Some(end),
+        }) = task.timer.intervals.last()
+        {
+            self.stats.record_focus_session(start, end - start);
+        }
+    }
+
+    /// Asks for a bell with `bell_on_complete`, outside quiet hours.
+    fn ring_bell(&mut self) {
+        let features = &self.config.features;
+        if features.bell_on_complete && !features.in_quiet_hours(Local::now().time()) {
+            self.bell_pending = true;
+        }
+    }
+
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell_pending)
+    }
+
+    /// Counts a finished session toward today's long-break streak and
+    /// suggests the long break exactly when the streak reaches the threshold.
+    fn record_work_session_at(&mut self, now: DateTime<Local>) {
+        let streak = self.stats.record_work_session(now);
+        if self.long_break_reminder_due(streak) {
+            let body = format!(
+                "{} sessions done. Take a {} minute break.",
+                streak, self.config.features.long_break_minutes
+            );
+            self.send_notification("Time for a long break", &body);
+        }
+    }
+
+    fn long_break_reminder_due(&self, streak: u32) -> bool {
+        let features = &self.config.features;
+        features.break_reminders && streak == features.long_break_after_sessions
+    }
+
+    /// Whether enough sessions have finished today that a long break is due.
+    pub fn long_break_due(&self) -> bool {
+        let threshold = self.config.features.long_break_after_sessions;
+        threshold > 0 && self.stats.sessions_since_long_break_today() >= threshold
+    }
+
+    /// How long task timers have kept running without a break of at least
+    /// `break_idle_minutes`.
+    pub fn continuous_work_duration(&self) -> Duration {
+        match (self.work_started, self.last_worked) {
+            (Some(start), Some(last)) => last - start,
+            _ => Duration::zero(),
+        }
+    }
+
+    fn track_work_at(&mut self, now: DateTime<Local>) {
+        let features = &self.config.features;
+        let idle_limit = Duration::minutes(features.break_idle_minutes);
+        let threshold = Duration::minutes(features.break_after_minutes);
+        let long_break = Duration::minutes(features.long_break_minutes);
+        let rested = self.last_worked.is_none_or(|last| now - last >= idle_limit);
+        let working = self
+            .tasks
+            .iter()
+            .any(|t| t.timer.state == TimerState::Running);
+
+        if !working
+            && self.long_break_due()
+            && self
+                .last_worked
+                .is_some_and(|last| now - last >= long_break)
+        {
+            self.stats.record_long_break(now);
+        }
+
+        if working {
+            if rested {
+                self.work_started = Some(now);
+                self.break_suggested = false;
+            }
+            self.last_worked = Some(now);
+        } else if rested {
+            // `last_worked` is kept so a long break can be measured from it.
+            self.work_started = None;
+            self.break_suggested = false;
+        }
+
+        if features.break_reminders
+            && !self.break_suggested
+            && self.work_started.is_some()
+            && self.continuous_work_duration() >= threshold
+        {
+            self.break_suggested = true;
+            let body = format!(
+                "You've been working for {} minutes straight.",
+                self.continuous_work_duration().num_minutes()
+            );
+            self.send_notification("Time for a break", &body);
+        }
+    }
+
+    fn send_notification(&mut self, title: &str, body: &str) {
+        let features = &self.config.features;
+        if !features.notification_sound
+            || self.notifications_unavailable
+            || features.in_quiet_hours(Local::now().time())
+        {
+            return;
+        }
+        let result = notify_rust::Notification::new()
+            .summary(title)
+            .body(body)
+            .appname("kronos")
+            .show()
+            .map(|_| ());
+        self.record_notification_result(result);
+    }
+
+    /// Counts a failed notification and reports it in the status bar rather
+    /// than printing it, which would corrupt the alternate screen. A failure
+    /// almost always means there is no notification daemon, so later
+    /// completions stop retrying.
+    fn record_notification_result<E: fmt::Display>(&mut self, result: Result<(), E>) {
+        if let Err(e) = result {
+            self.notification_failures += 1;
+            self.notifications_unavailable = true;
+            self.status_message = Some(format!("Desktop notifications unavailable: {}", e));
+        }
+    }
+
+    pub fn get_preset_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.presets.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn get_category_names(&self) -> Vec<String> {
+        [
+            "Work",
+            "Personal",
+            "Study",
+            "Exercise",
+            "General",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    /// Preset names matching the picker query, best match first.
+    pub fn filtered_preset_names(&self) -> Vec<String> {
+        let names = self.get_preset_names();
+        fuzzy_filter(&names, &self.input_buffer)
+            .into_iter()
+            .map(|i| names[i].clone())
+            .collect()
+    }
+
+    /// Category names matching the picker query, best match first.
+    pub fn filtered_category_names(&self) -> Vec<String> {
+        let names = self.get_category_names();
+        fuzzy_filter(&names, &self.input_buffer)
+            .into_iter()
+            .map(|i| names[i].clone())
+            .collect()
+    }
+
+    pub fn set_task_category(&mut self, task_idx: usize, category: TaskCategory) {
+        if let Some(task) = self.tasks.get_mut(task_idx) {
+            let from = std::mem::replace(&mut task.category, category.clone());
+            let id = task.id;
+            if from != category {
+                self.record_action(Action::Category {
+                    id,
+                    from,
+                    to: category,
+                });
+            }
+        }
+    }
+
+    /// Adds a subtask per non-blank line to a task, which keeps its own
+    /// timer. With `divide_duration` the task's timer length is shared out
+    /// among the new subtasks, earlier ones taking any leftover minutes.
+    /// Returns how many subtasks were added.
+    pub fn split_task(
+        &mut self,
+        task_idx: usize,
+        lines: Vec<String>,
+        divide_duration: bool,
+    ) -> usize {
+        let Some(task) = self.tasks.get_mut(task_idx) else {
+            return 0;
+        };
+        let lines: Vec<String> = lines
+            .into_iter()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        let count = lines.len() as i64;
+        let total = task.timer.target_duration.num_minutes();
+        for (i, description) in lines.into_iter().enumerate() {
+            let target_minutes = (divide_duration && count > 0)
+                .then(|| total / count + i64::from((i as i64) < total % count));
+            task.subtasks.push(Subtask {
+                description,
+                completed: false,
+                target_minutes,
+            });
+        }
+        count as usize
+    }
+
+    /// Flips a subtask between done and not done. Returns its new state, or
+    /// `None` if the task or subtask doesn't exist.
+    pub fn toggle_subtask(&mut self, task_idx: usize, subtask_idx: usize) -> Option<bool> {
+        let subtask = self
+            .tasks
+            .get_mut(task_idx)?
+            .subtasks
+            .get_mut(subtask_idx)?;
+        subtask.completed = !subtask.completed;
+        Some(subtask.completed)
+    }
+
+    pub fn start_split_selected_task(&mut self) {
+        if self.tasks.get(self.selected_task).is_some() {
+            self.split_lines.clear();
+            self.input_buffer.clear();
+            self.mode = AppMode::SplittingTask(self.selected_task);
+        }
+    }
+
+    /// Gives a task a new description. Blank descriptions are ignored.
+    pub fn rename_task(&mut self, task_idx: usize, description: &str) {
+        let description = description.trim();
+        let Some(task) = self.tasks.get_mut(task_idx) else {
+            return;
+        };
+        if description.is_empty() || description == task.description {
+            return;
+        }
+        let from = std::mem::replace(&mut task.description, description.to_string());
+        let id = task.id;
+        self.record_action(Action::Rename {
+            id,
+            from,
+            to: description.to_string(),
+        });
+    }
+
+    /// Opens the rename prompt with the selected task's description to edit.
+    pub fn start_rename_selected_task(&mut self) {
+        if let Some(task) = self.tasks.get(self.selected_task) {
+            self.input_buffer = task.description.clone();
+            self.mode = AppMode::RenamingTask(self.selected_task);
+        }
+    }
+
+    /// Opens the due date prompt with the selected task's current due date.
+    pub fn start_edit_due_selected_task(&mut self) {
+        if let Some(task) = self.tasks.get(self.selected_task) {
+            self.input_buffer = task.due.map(|due| due.to_string()).unwrap_or_default();
+            self.mode = AppMode::EditingDue(self.selected_task);
+        }
+    }
+
+    /// Sets or, with an empty `input`, clears a task's due date. `input` is
+    /// `YYYY-MM-DD`.
+    pub fn set_task_due(&mut self, task_idx: usize, input: &str) -> Result<()> {
+        let input = input.trim();
+        let due = if input.is_empty() {
+            None
+        } else {
+            Some(
+                NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                    .with_context(|| format!("Invalid due date {:?}, use YYYY-MM-DD", input))?,
+            )
+        };
+        if let Some(task) = self.tasks.get_mut(task_idx) {
+            task.due = due;
+        }
+        Ok(())
+    }
+
+    /// Leaves split mode without adding any of the lines entered so far.
+    pub fn cancel_split_task(&mut self) {
+        self.split_lines.clear();
+        self.input_buffer.clear();
+        self.mode = AppMode::Normal;
+    }
+
+    /// Moves the selected task to the next built-in category: Work, Personal,
+    /// Study, Exercise, General and back to Work. Any custom category goes
+    /// to Work.
+    pub fn cycle_selected_task_category(&mut self) {
+        if let Some(task) = self.tasks.get(self.selected_task) {
+            let next = match task.category {
+                TaskCategory::Work => TaskCategory::Personal,
+                TaskCategory::Personal => TaskCategory::Study,
+                TaskCategory::Study => TaskCategory::Exercise,
+                TaskCategory::Exercise => TaskCategory::Other("General".to_string()),
+                TaskCategory::Other(_) => TaskCategory::Work,
+            };
+            self.set_task_category(self.selected_task, next);
+        }
+    }
+
+    /// Every `trigger_*` method goes through here so that disabling effects
+    /// turns them all into no-ops.
+    fn add_effect(&mut self, effect: Effect) {
+        if self.effects_active() {
+            self.effect_manager.add_effect(effect);
+        }
+    }
+
+    pub fn trigger_startup_animation(&mut self) {
+        let startup_ms = self.config.effects.startup;
+        let ends_in = if self.effects_active() {
+            std::time::Duration::from_millis(startup_ms.into())
+        } else {
+            std::time::Duration::ZERO
+        };
+        self.startup_ends_at = Some(Instant::now() + ends_in);
+        self.add_effect(fx::sweep_in(
+            Motion::UpToDown,
+            20,
+            0,
+            self.config.theme.selection,
+            startup_ms,
+        ));
+    }
+
+    /// The mode for the configured `startup_view`.
+    pub fn startup_mode(&self) -> AppMode {
+        match self.config.features.startup_view {
+            StartupView::Tasks => AppMode::Normal,
+            StartupView::Stats => AppMode::ShowStats,
+            StartupView::Help => AppMode::ShowHelp,
+        }
+    }
+
+    /// Leaves the startup animation for the startup view once it has run.
+    pub fn finish_startup_at(&mut self, now: Instant) {
+        if self.mode == AppMode::StartupAnimation
+            && self.startup_ends_at.is_none_or(|ends_at| now >= ends_at)
+        {
+            self.mode = self.startup_mode();
+        }
+    }
+
+    pub fn trigger_mode_change_effect(&mut self, area: Rect) {
+        let effect = fx::slide_in(
+            Motion::LeftToRight,
+            8,
+            4,
+            self.config.theme.selection,
+            self.config.effects.mode_change,
+        )
+        .with_area(area);
+        self.add_effect(effect);
+    }
+
+    /// The dissolve played over a deleted task, lasting `effects.delete`.
+    fn delete_effect(&self) -> Effect {
+        fx::dissolve(self.config.effects.delete)
+    }
+
+    /// The dissolve played over a completed task, lasting `effects.complete`.
+    fn complete_effect(&self) -> Effect {
+        fx::dissolve(self.config.effects.complete)
+    }
+
+    pub fn trigger_delete_effect(&mut self, area: Rect) {
+        let effect = self.delete_effect().with_area(area);
+        self.add_effect(effect);
+    }
+
+    pub fn trigger_complete_effect(&mut self, area: Rect) {
+        let effect = self.complete_effect().with_area(area);
+        self.add_effect(effect);
+    }
+
+    pub fn trigger_task_complete_celebration(&mut self, area: Rect) {
+        let effect = fx::fade_to_fg(self.config.theme.green, self.config.effects.celebration);
+        self.add_effect(effect.with_area(area));
+    }
+
+    /// Briefly brightens a running task's state icon and time, at most once
+    /// per `RUNNING_PULSE_INTERVAL`.
+    pub fn trigger_running_pulse(&mut self, area: Rect) {
+        if !self.running_pulse_due(Instant::now()) {
+            return;
+        }
+        let fade = fx::fade_to_fg(
+            self.config.theme.foreground,
+            self.config.effects.running_pulse,
+        );
+        let effect = fx::ping_pong(fade)
+            .with_area(area)
+            .with_filter(CellFilter::FgColor(self.config.theme.cyan));
+        self.add_effect(effect);
+    }
+
+    /// Records `now` as the last pulse if the previous one is old enough.
+    fn running_pulse_due(&mut self, now: Instant) -> bool {
+        if self
+            .last_running_pulse
+            .is_some_and(|last| now.duration_since(last) < RUNNING_PULSE_INTERVAL)
+        {
+            return false;
+        }
+        self.last_running_pulse = Some(now);
+        true
+    }
+
+    #[allow(dead_code)]
+    pub fn trigger_streak_animation(&mut self, area: Rect) {
+        let effect = fx::fade_to_fg(self.config.theme.magenta, self.config.effects.streak);
+        self.add_effect(effect.with_area(area));
+    }
+
+    #[allow(dead_code)]
+    pub fn show_stats_summary(&self) -> String {
+        format!(
+            "📊 Total: {} tasks | ⏱️  {} hours | 🔥 {} day streak",
+            self.stats.total_completed,
+            self.stats.total_time_worked.num_hours(),
+            self.stats.daily_streak
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn export_to_csv(&self) -> Result<String, std::fmt::Error> {
+        let features = &self.config.features;
+        let mut csv =
+            String::from("Task,Category,Priority,Time Spent,Completed,Created,Completed At\n");
+        for task in &self.tasks {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                task.description,
+                task.category,
+                task.priority,
+                task.timer.get_elapsed().num_minutes(),
+                task.completed,
+                features.format_datetime(task.created_at),
+                task.completed_at
+                    .map_or("N/A".to_string(), |d| features.format_datetime(d))
+            ));
+        }
+        Ok(csv)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// An app past its startup animation that won't pop up desktop
+    /// notifications or write worklog files.
+    pub(crate) fn test_app() -> App {
+        let mut config = Config::default();
+        config.features.notification_sound = false;
+        config.features.worklog = false;
+        let mut app = App::new(config);
+        app.mode = AppMode::Normal;
+        app
+    }
+
+    fn task_with_elapsed(app: &mut App, minutes: i64) -> Task {
+        app.add_task("Write report".to_string()).unwrap();
+        let mut task = app.tasks.pop().unwrap();
+        task.timer.accumulated_time = Duration::minutes(minutes);
+        task
+    }
+
+    #[test]
+    fn completed_pomodoros_counts_whole_pomodoros_only() {
+        let mut app = test_app();
+        let mut task = task_with_elapsed(&mut app, 60);
+        task.estimated_pomodoros = 4;
+        assert_eq!(task.completed_pomodoros(25), 2);
+        assert_eq!(task_with_elapsed(&mut app, 24).completed_pomodoros(25), 0);
+    }
+
+    #[test]
+    fn completed_pomodoros_can_exceed_the_estimate() {
+        let mut app = test_app();
+        let mut task = task_with_elapsed(&mut app, 100);
+        task.estimated_pomodoros = 2;
+        assert_eq!(task.completed_pomodoros(25), 4);
+        assert_eq!(task.completed_pomodoros(0), 0);
+    }
+
+    #[test]
+    fn add_preset_rejects_duplicate_names() {
+        let mut app = test_app();
+        assert!(app.add_preset("Deep Work", 50));
+        assert!(!app.add_preset("Deep Work", 90));
+        assert!(!app.add_preset(" Pomodoro ", 30));
+        assert_eq!(app.presets["Deep Work"], 50);
+        assert_eq!(app.presets["Pomodoro"], 25);
+    }
+
+    #[test]
+    fn remove_preset_only_removes_existing_ones() {
+        let mut app = test_app();
+        assert!(app.remove_preset("Short Break"));
+        assert!(!app.presets.contains_key("Short Break"));
+        assert!(!app.remove_preset("Short Break"));
+    }
+
+    #[test]
+    fn worklog_is_off_by_default() {
+        assert!(!Config::default().features.worklog);
+    }
+
+    #[test]
+    fn append_worklog_adds_a_line_per_completion() {
+        let dir = std::env::temp_dir().join(format!("kronos-worklog-{}", std::process::id()));
+        let mut app = test_app();
+        app.config.features.worklog_dir = Some(dir.to_string_lossy().into_owned());
+        let completed_at = Local::now();
+        for (description, minutes) in [("Write report", 30), ("Send report", 5)] {
+            let mut task = task_with_elapsed(&mut app, minutes);
+            task.description = description.to_string();
+            task.completed_at = Some(completed_at);
+            app.append_worklog(&task).unwrap();
+        }
+
+        let path = dir.join(format!("{}.log", completed_at.format("%Y-%m-%d")));
+        let log = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let time = completed_at.format("%H:%M");
+        let expected = format!("{0}  Write report  (30m)\n{0}  Send report  (5m)\n", time);
+        assert_eq!(log, expected);
+    }
+
+    #[test]
+    fn snooze_extends_the_target_and_rearms_the_notification() {
+        let mut app = test_app();
+        let task = task_with_elapsed(&mut app, 25);
+        let id = task.id;
+        app.tasks.push(task);
+        app.tasks[0].timer.start();
+        app.notifications_sent.push(id);
+
+        app.snooze_selected_timer(5);
+        let timer = &app.tasks[0].timer;
+        assert_eq!(timer.target_duration, Duration::minutes(30));
+        assert_eq!(timer.state, TimerState::Running);
+        assert!(!timer.is_complete());
+        assert!(!app.notifications_sent.contains(&id));
+    }
+
+    fn descriptions(app: &App) -> Vec<&str> {
+        app.tasks.iter().map(|t| t.description.as_str()).collect()
+    }
+
+    fn app_with_tasks(descriptions: &[&str]) -> App {
+        let mut app = test_app();
+        for description in descriptions {
+            app.add_task(description.to_string()).unwrap();
+        }
+        app
+    }
+
+    #[test]
+    fn insert_above_the_first_task() {
+        let mut app = app_with_tasks(&["b", "c"]);
+        app.add_task_at(0, "a".to_string()).unwrap();
+        assert_eq!(descriptions(&app), ["a", "b", "c"]);
+        assert_eq!(app.selected_task, 0);
+        assert_eq!(app.tasks[0].id, 3);
+    }
+
+    #[test]
+    fn insert_below_the_last_task() {
+        let mut app = app_with_tasks(&["a", "b"]);
+        app.add_task_at(2, "c".to_string()).unwrap();
+        assert_eq!(descriptions(&app), ["a", "b", "c"]);
+        assert_eq!(app.selected_task, 2);
+        app.add_task_at(99, "d".to_string()).unwrap();
+        assert_eq!(descriptions(&app), ["a", "b", "c", "d"]);
+        assert_eq!(app.selected_task, 3);
+    }
+
+    /// Whether the app's effects change anything over `ms` milliseconds of a
+    /// filled-in buffer; the effect manager can't be inspected directly.
+    fn effects_draw(app: &mut App, area: Rect, ms: u32) -> bool {
+        let mut buffer = ratatui::buffer::Buffer::filled(area, ratatui::buffer::Cell::new("x"));
+        let before = buffer.clone();
+        app.effect_manager
+            .process_effects(tachyonfx::Duration::from_millis(ms), &mut buffer, area);
+        buffer != before
+    }
+
+    #[test]
+    fn delete_effect_is_skipped_with_effects_disabled() {
+        let area = Rect::new(0, 0, 10, 2);
+        let mut app = test_app();
+        app.config.features.effects_enabled = Some(false);
+        app.effect_manager = default_effect_manager();
+        app.trigger_delete_effect(area);
+        assert!(!effects_draw(&mut app, area, 10_000));
+
+        app.config.features.effects_enabled = Some(true);
+        app.trigger_delete_effect(area);
+        assert!(effects_draw(&mut app, area, 10_000));
+    }
+
+    #[test]
+    fn next_and_prev_incomplete_wrap_around() {
+        let mut app = app_with_tasks(&["a", "b", "c", "d"]);
+        app.tasks[1].completed = true;
+        app.tasks[2].completed = true;
+        app.selected_task = 3;
+        app.select_next_incomplete();
+        assert_eq!(app.selected_task, 0);
+        app.select_prev_incomplete();
+        assert_eq!(app.selected_task, 3);
+        app.select_next_incomplete();
+        app.select_next_incomplete();
+        assert_eq!(app.selected_task, 3);
+    }
+
+    #[test]
+    fn next_incomplete_is_a_no_op_when_everything_is_done() {
+        let mut app = app_with_tasks(&["a", "b", "c"]);
+        for task in &mut app.tasks {
+            task.completed = true;
+        }
+        app.selected_task = 1;
+        app.select_next_incomplete();
+        assert_eq!(app.selected_task, 1);
+        app.select_prev_incomplete();
+        assert_eq!(app.selected_task, 1);
+
+        let mut empty = test_app();
+        empty.select_next_incomplete();
+        empty.select_prev_incomplete();
+        assert_eq!(empty.selected_task, 0);
+    }
+
+    /// 2026-03-02 (a Monday) at `hour:min`, local time.
+    fn at(hour: u32, min: u32) -> DateTime<Local> {
+        use chrono::TimeZone;
+        Local
+            .with_ymd_and_hms(2026, 3, 2, hour, min, 0)
+            .single()
+            .unwrap()
+    }
+
+    #[test]
+    fn pause_and_resume_close_and_open_intervals() {
+        let mut timer = Timer::new(25);
+        timer.start_at(at(9, 0));
+        timer.pause_at(at(9, 10));
+        timer.resume_at(at(9, 30));
+        timer.pause_at(at(9, 45));
+        assert_eq!(
+            timer.intervals,
+            [
+                Interval {
+                    start: at(9, 0),
+                    end: Some(at(9, 10)),
+                },
+                Interval {
+                    start: at(9, 30),
+                    end: Some(at(9, 45)),
+                },
+            ]
+        );
+        assert_eq!(timer.accumulated_time, Duration::minutes(25));
+    }
+
+    #[test]
+    fn completing_a_finished_task_starts_the_next_with_auto_advance() {
+        let mut app = app_with_tasks(&["a", "b", "c"]);
+        app.config.features.auto_advance = true;
+        app.tasks[1].completed = true;
+        app.tasks[0].timer.accumulated_time = Duration::minutes(25);
+        app.tasks[0].timer.start();
+
+        app.toggle_selected_task_completion();
+        assert!(app.tasks[0].completed);
+        assert_eq!(app.tasks[0].timer.state, TimerState::Paused);
+        assert_eq!(app.tasks[1].timer.state, TimerState::Idle);
+        assert_eq!(app.tasks[2].timer.state, TimerState::Running);
+        assert_eq!(app.selected_task, 2);
+    }
+
+    #[test]
+    fn auto_advance_is_opt_in() {
+        let mut app = app_with_tasks(&["a", "b"]);
+        app.tasks[0].timer.accumulated_time = Duration::minutes(25);
+        app.tasks[0].timer.start();
+        app.toggle_selected_task_completion();
+        assert_eq!(app.tasks[1].timer.state, TimerState::Idle);
+        assert_eq!(app.selected_task, 0);
+    }
+
+    #[test]
+    fn import_json_gives_fresh_ids_and_skips_invalid_tasks() {
+        let mut app = app_with_tasks(&["existing"]);
+        let json = r#"[
+            {"description": "Write report", "category": "Work", "priority": "high", "minutes": 50},
+            {"description": "Read", "estimated_pomodoros": 2},
+            {"description": "Bad", "priority": "someday"},
+            {"category": "Work"}
+        ]"#;
+        let summary = app.import_from_json(json).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 2,
+                skipped: 2
+            }
+        );
+        let report = &app.tasks[1];
+        assert_eq!(report.id, 2);
+        assert_eq!(report.category, TaskCategory::Work);
+        assert!(report.priority == Priority::High);
+        assert_eq!(report.timer.target_duration, Duration::minutes(50));
+        assert_eq!(app.tasks[2].id, 3);
+        assert_eq!(app.tasks[2].estimated_pomodoros, 2);
+        assert!(app.import_from_json("{}").is_err());
+    }
+
+    #[test]
+    fn import_csv_skips_a_bad_row() {
+        let mut app = test_app();
+        let csv = "description,category,priority,minutes\n\
+                   Write report,Work,urgent,45\n\
+                   Stretch,,,ten\n\
+                   Read,Study,,\n";
+        let summary = app.import_from_csv(csv);
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 2,
+                skipped: 1
+            }
+        );
+        assert_eq!(descriptions(&app), ["Write report", "Read"]);
+        assert_eq!(app.tasks[1].category, TaskCategory::Study);
+    }
+
+    #[test]
+    fn global_preset_sets_the_length_and_resets_the_timer() {
+        let mut app = test_app();
+        app.global_timer.accumulated_time = Duration::minutes(20);
+        app.global_timer.start();
+        app.notifications_sent.push(0);
+
+        app.set_global_duration_from_preset("Long Break");
+        assert_eq!(app.global_timer.target_duration, Duration::minutes(15));
+        assert_eq!(app.global_timer.state, TimerState::Idle);
+        assert_eq!(app.global_timer.get_elapsed(), Duration::zero());
+        assert!(app.notifications_sent.is_empty());
+
+        app.set_global_duration_from_preset("No such preset");
+        assert_eq!(app.global_timer.target_duration, Duration::minutes(15));
+    }
+
+    #[test]
+    fn completions_count_toward_the_day_they_happened() {
+        let mut stats = Stats::default();
+        let before_midnight = at(23, 59);
+        let after_midnight = before_midnight + Duration::minutes(2);
+        stats.record_completion(before_midnight);
+        stats.record_completion(after_midnight);
+        stats.record_completion(after_midnight);
+        assert_eq!(stats.completed_on(before_midnight.date_naive()), 1);
+        assert_eq!(stats.completed_on(after_midnight.date_naive()), 2);
+
+        let mut stats = Stats::default();
+        stats.record_completion(Local::now() - Duration::days(1));
+        assert_eq!(stats.completed_today(), 0);
+        stats.record_completion(Local::now());
+        assert_eq!(stats.completed_today(), 1);
+    }
+
+    fn finished_global_timer(global_alarm: bool) -> App {
+        let mut app = test_app();
+        app.config.features.global_alarm = global_alarm;
+        app.global_timer.accumulated_time = Duration::minutes(25);
+        app.global_timer.start();
+        app.check_and_notify_completions();
+        app
+    }
+
+    #[test]
+    fn global_alarm_pauses_at_zero() {
+        let app = finished_global_timer(true);
+        assert_eq!(app.global_timer.state, TimerState::Paused);
+        assert!(app.notifications_sent.contains(&0));
+    }
+
+    #[test]
+    fn global_timer_runs_into_overrun_without_alarm() {
+        let app = finished_global_timer(false);
+        assert_eq!(app.global_timer.state, TimerState::Running);
+        assert!(app.notifications_sent.contains(&0));
+    }
+
+    #[test]
+    fn failed_notification_is_counted_and_not_retried() {
+        let mut app = test_app();
+        app.record_notification_result(Err("no notification daemon"));
+        assert_eq!(app.notification_failures, 1);
+        assert!(app.notifications_unavailable);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Desktop notifications unavailable: no notification daemon")
+        );
+
+        app.config.features.notification_sound = true;
+        app.send_notification("Write report", "Task timer completed!");
+        assert_eq!(app.notification_failures, 1);
+    }
+
+    /// Runs the per-frame work tracking once a minute from `from` to `to`.
+    fn track_work_between(app: &mut App, from: DateTime<Local>, to: DateTime<Local>) {
+        let mut now = from;
+        while now <= to {
+            app.track_work_at(now);
+            now += Duration::minutes(1);
+        }
+    }
+
+    #[test]
+    fn break_is_suggested_once_work_crosses_the_threshold() {
+        let mut app = app_with_tasks(&["a"]);
+        app.tasks[0].timer.start_at(at(9, 0));
+        track_work_between(&mut app, at(9, 0), at(9, 59));
+        assert_eq!(app.continuous_work_duration(), Duration::minutes(59));
+        assert!(!app.break_suggested);
+        app.track_work_at(at(10, 0));
+        assert_eq!(app.continuous_work_duration(), Duration::minutes(60));
+        assert!(app.break_suggested);
+    }
+
+    #[test]
+    fn continuous_work_resets_after_an_idle_break() {
+        let mut app = app_with_tasks(&["a"]);
+        app.tasks[0].timer.start_at(at(9, 0));
+        track_work_between(&mut app, at(9, 0), at(10, 0));
+        app.tasks[0].timer.pause_at(at(10, 0));
+
+        // A pause shorter than break_idle_minutes doesn't count as a break.
+        track_work_between(&mut app, at(10, 1), at(10, 4));
+        assert_eq!(app.continuous_work_duration(), Duration::minutes(60));
+        app.track_work_at(at(10, 5));
+        assert_eq!(app.continuous_work_duration(), Duration::zero());
+        assert!(!app.break_suggested);
+
+        app.tasks[0].timer.start_at(at(10, 6));
+        track_work_between(&mut app, at(10, 6), at(10, 30));
+        assert_eq!(app.continuous_work_duration(), Duration::minutes(24));
+    }
+
+    #[test]
+    fn duplicate_gets_a_new_id_and_a_fresh_timer() {
+        let mut app = app_with_tasks(&["Write report", "Send report"]);
+        app.set_task_category(0, TaskCategory::Work);
+        app.set_task_duration(0, 50);
+        let original = &mut app.tasks[0];
+        original.priority = Priority::Urgent;
+        original.completed = true;
+        original.timer.accumulated_time = Duration::minutes(20);
+        original.timer.start();
+
+        app.duplicate_selected_task();
+        assert_eq!(
+            descriptions(&app),
+            ["Write report", "Write report (copy)", "Send report"]
+        );
+        assert_eq!(app.selected_task, 1);
+        let copy = &app.tasks[1];
+        assert_eq!(copy.id, 3);
+        assert_eq!(copy.category, TaskCategory::Work);
+        assert!(copy.priority == Priority::Urgent);
+        assert_eq!(copy.timer.target_duration, Duration::minutes(50));
+        assert_eq!(copy.timer.state, TimerState::Idle);
+        assert_eq!(copy.timer.get_elapsed(), Duration::zero());
+        assert!(!copy.completed);
+    }
+
+    #[test]
+    fn selection_follows_its_task_after_an_external_insert() {
+        let mut app = app_with_tasks(&["a", "b", "c"]);
+        app.selected_task = 1;
+        app.anchor_selection();
+        app.add_task_at(0, "new".to_string()).unwrap();
+        app.reconcile_selection();
+        assert_eq!(app.tasks[app.selected_task].description, "b");
+        assert_eq!(app.selected_task_id, Some(2));
+    }
+
+    #[test]
+    fn selection_stays_in_range_after_an_external_delete() {
+        let mut app = app_with_tasks(&["a", "b", "c"]);
+        app.selected_task = 2;
+        app.anchor_selection();
+        app.tasks.retain(|t| t.description != "c");
+        app.reconcile_selection();
+        assert_eq!(app.selected_task, 1);
+        assert_eq!(app.selected_task_id, Some(2));
+
+        app.selected_task = 0;
+        app.anchor_selection();
+        app.tasks.remove(1);
+        app.reconcile_selection();
+        assert_eq!(app.tasks[app.selected_task].description, "a");
+    }
+
+    /// A task created at `created` and, if `completed` is set, completed then.
+    fn task_at(
+        app: &mut App,
+        created: DateTime<Local>,
+        completed: Option<DateTime<Local>>,
+    ) -> Task {
+        let mut task = task_with_elapsed(app, 0);
+        task.created_at = created;
+        task.completed = completed.is_some();
+        task.completed_at = completed;
+        task
+    }
+
+    #[test]
+    fn backlog_change_counts_additions_against_completions() {
+        let mut app = test_app();
+        let today = at(12, 0);
+        let yesterday = today - Duration::days(1);
+        let tasks = [
+            // Added today, still open.
+            task_at(&mut app, at(9, 0), None),
+            task_at(&mut app, at(10, 0), None),
+            // Added yesterday, done today.
+            task_at(&mut app, yesterday, Some(at(11, 0))),
+        ];
+        assert_eq!(Stats::net_backlog_change(&tasks, today.date_naive()), 1);
+        assert_eq!(Stats::net_backlog_change(&tasks, yesterday.date_naive()), 1);
+    }
+
+    #[test]
+    fn backlog_change_cancels_out_for_same_day_tasks() {
+        let mut app = test_app();
+        let today = at(12, 0).date_naive();
+        let tasks = [
+            task_at(&mut app, at(9, 0), Some(at(10, 0))),
+            task_at(&mut app, at(9, 0) - Duration::days(2), Some(at(11, 0))),
+        ];
+        assert_eq!(Stats::net_backlog_change(&tasks, today), -1);
+        assert_eq!(Stats::net_backlog_change(&[], today), 0);
+    }
+
+    /// A timer in `state` with 10 minutes on it.
+    fn timer_in(state: TimerState) -> Timer {
+        let mut timer = Timer::new(25);
+        if state != TimerState::Idle {
+            timer.start_at(at(9, 0));
+        }
+        if state == TimerState::Paused {
+            timer.pause_at(at(9, 10));
+        }
+        timer
+    }
+
+    #[test]
+    fn explicit_transitions_from_every_state() {
+        use TimerState::{Idle, Paused, Running};
+        // (from, start, pause, resume, stop)
+        let cases = [
+            (Idle, Running, Idle, Idle, Idle),
+            (Running, Running, Paused, Running, Idle),
+            (Paused, Running, Paused, Running, Idle),
+        ];
+        for (from, start, pause, resume, stop) in cases {
+            let mut timer = timer_in(from.clone());
+            timer.start_at(at(9, 20));
+            assert_eq!(timer.state, start, "start from {:?}", from);
+            let mut timer = timer_in(from.clone());
+            timer.pause_at(at(9, 20));
+            assert_eq!(timer.state, pause, "pause from {:?}", from);
+            let mut timer = timer_in(from.clone());
+            timer.resume_at(at(9, 20));
+            assert_eq!(timer.state, resume, "resume from {:?}", from);
+            let mut timer = timer_in(from.clone());
+            timer.stop_at(at(9, 20));
+            assert_eq!(timer.state, stop, "stop from {:?}", from);
+        }
+    }
+
+    #[test]
+    fn starting_a_running_timer_keeps_its_start() {
+        let mut timer = timer_in(TimerState::Running);
+        timer.start_at(at(9, 5));
+        assert_eq!(timer.started_at, Some(at(9, 0)));
+        assert_eq!(timer.intervals.len(), 1);
+    }
+
+    #[test]
+    fn running_pulse_fires_at_most_once_per_interval() {
+        let mut app = test_app();
+        let start = Instant::now();
+        assert!(app.running_pulse_due(start));
+        assert!(!app.running_pulse_due(start + RUNNING_PULSE_INTERVAL / 2));
+        assert!(!app.running_pulse_due(start + RUNNING_PULSE_INTERVAL / 2));
+        assert!(app.running_pulse_due(start + RUNNING_PULSE_INTERVAL));
+        assert!(!app.running_pulse_due(start + RUNNING_PULSE_INTERVAL * 3 / 2));
+    }
+
+    #[test]
+    fn start_all_starts_only_incomplete_idle_or_paused_tasks() {
+        use TimerState::{Idle, Running};
+        let mut app = app_with_tasks(&["idle", "paused", "running", "done", "work"]);
+        app.tasks[1].timer.start_at(at(9, 0));
+        app.tasks[1].timer.pause_at(at(9, 5));
+        app.tasks[2].timer.start();
+        app.tasks[3].completed = true;
+        app.tasks[4].category = TaskCategory::Work;
+
+        assert_eq!(app.start_all_pending(Some(&TaskCategory::Work)), 1);
+        assert_eq!(app.start_all_pending(None), 2);
+        let states: Vec<TimerState> = app.tasks.iter().map(|t| t.timer.state.clone()).collect();
+        assert_eq!(states, [Running, Running, Running, Idle, Running]);
+        assert_eq!(app.start_all_pending(None), 0);
+    }
+
+    #[test]
+    fn terminal_title_shows_the_running_task() {
+        let mut app = app_with_tasks(&["Write report", "Read mail"]);
+        assert_eq!(app.terminal_title_at(at(9, 0)), "kronos");
+
+        let timer = &mut app.tasks[0].timer;
+        timer.target_duration = Duration::minutes(25);
+        timer.accumulated_time = Duration::minutes(10);
+        timer.start_at(at(9, 0));
+        let title = app.terminal_title_at(at(9, 5) + Duration::seconds(1));
+        assert_eq!(title, "kronos - Write report 09:59 (60%)");
+
+        app.tasks[0].timer.pause_at(at(9, 6));
+        let timer = &mut app.tasks[1].timer;
+        timer.target_duration = Duration::zero();
+        timer.accumulated_time = Duration::minutes(2);
+        timer.start_at(at(9, 6));
+        assert_eq!(app.terminal_title_at(at(9, 9)), "kronos - Read mail ↑05:00");
+    }
+
+    #[test]
+    fn presets_reset_or_keep_elapsed_time() {
+        for keep in [false, true] {
+            let mut app = app_with_tasks(&["Write report"]);
+            app.config.features.keep_elapsed_on_preset = keep;
+            let timer = &mut app.tasks[0].timer;
+            timer.start_at(at(9, 0));
+            timer.pause_at(at(9, 10));
+
+            app.set_task_duration_from_preset(0, "Long Break");
+            let timer = &app.tasks[0].timer;
+            assert_eq!(timer.target_duration, Duration::minutes(15));
+            let (elapsed, state) = if keep {
+                (Duration::minutes(10), TimerState::Paused)
+            } else {
+                (Duration::zero(), TimerState::Idle)
+            };
+            assert_eq!(timer.accumulated_time, elapsed, "keep = {}", keep);
+            assert_eq!(timer.state, state, "keep = {}", keep);
+        }
+    }
+
+    #[test]
+    fn category_breakdown_sums_completed_tasks_per_category() {
+        let mut app = app_with_tasks(&["a", "b", "c", "d"]);
+        let setup = [
+            (TaskCategory::Work, 30, true),
+            (TaskCategory::Work, 20, true),
+            (TaskCategory::Study, 45, true),
+            (TaskCategory::Study, 60, false),
+        ];
+        for (task, (category, minutes, completed)) in app.tasks.iter_mut().zip(setup) {
+            task.category = category;
+            task.timer.accumulated_time = Duration::minutes(minutes);
+            task.completed = completed;
+        }
+        app.stats
+            .tasks_by_category
+            .insert(TaskCategory::Exercise, 2);
+
+        let expected = [("Exercise", 0), ("Study", 45), ("Work", 50)]
+            .map(|(category, minutes)| (category.to_string(), minutes));
+        assert_eq!(app.category_breakdown(), expected);
+    }
+
+    #[test]
+    fn tips_rotate_every_interval_and_wrap() {
+        let mut app = test_app();
+        let start = Instant::now();
+        app.rotate_tip_at(start);
+        assert_eq!(app.current_tip(), TIPS[0]);
+        app.rotate_tip_at(start + TIP_INTERVAL / 2);
+        assert_eq!(app.current_tip(), TIPS[0]);
+
+        for i in 1..=TIPS.len() as u32 {
+            app.rotate_tip_at(start + TIP_INTERVAL * i);
+            assert_eq!(app.current_tip(), TIPS[i as usize % TIPS.len()]);
+        }
+        assert_eq!(app.current_tip(), TIPS[0]);
+    }
+
+    #[test]
+    fn adding_past_max_tasks_is_rejected() {
+        let mut app = test_app();
+        app.config.features.max_tasks = Some(2);
+        app.add_task("a".to_string()).unwrap();
+        app.add_task_at(0, "b".to_string()).unwrap();
+        let err = app.add_task("c".to_string()).unwrap_err();
+        assert_eq!(err.to_string(), "Task limit of 2 reached");
+        assert!(app.add_task_at(0, "c".to_string()).is_err());
+        assert_eq!(descriptions(&app), ["b", "a"]);
+
+        app.tasks.pop();
+        app.add_task("c".to_string()).unwrap();
+        assert_eq!(descriptions(&app), ["b", "c"]);
+    }
+
+    #[test]
+    fn laps_record_increasing_elapsed_and_clear_on_reset() {
+        let mut timer = Timer::new(25);
+        timer.lap();
+        assert!(timer.laps.is_empty());
+
+        timer.start_at(at(9, 0));
+        timer.pause_at(at(9, 5));
+        timer.lap();
+        timer.resume_at(at(9, 10));
+        timer.pause_at(at(9, 17));
+        timer.lap();
+        assert_eq!(timer.laps, [Duration::minutes(5), Duration::minutes(12)]);
+        assert_eq!(timer.state, TimerState::Paused);
+
+        timer.reset();
+        assert!(timer.laps.is_empty());
+    }
+
+    #[test]
+    fn worked_on_sums_only_that_days_sessions() {
+        let mut app = app_with_tasks(&["a", "b"]);
+        let yesterday = at(9, 0) - Duration::days(1);
+        let timer = &mut app.tasks[0].timer;
+        timer.start_at(yesterday);
+        timer.pause_at(yesterday + Duration::minutes(50));
+        timer.resume_at(at(9, 0));
+        timer.pause_at(at(9, 25));
+        app.tasks[1].timer.start_at(at(10, 0));
+
+        let worked = Stats::worked_on(&app.tasks, at(0, 0).date_naive(), at(10, 15));
+        assert_eq!(worked, Duration::minutes(40));
+    }
+
+    #[test]
+    fn long_break_reminder_fires_once_at_four_sessions() {
+        let mut app = test_app();
+        let now = Local::now();
+        let reminders: Vec<bool> = (0..6)
+            .map(|_| {
+                let streak = app.stats.record_work_session(now);
+                app.long_break_reminder_due(streak)
+            })
+            .collect();
+        assert_eq!(reminders, [false, false, false, true, false, false]);
+        assert_eq!(app.stats.work_sessions_today(), 6);
+        assert!(app.long_break_due());
+
+        app.stats.record_long_break(now);
+        assert!(!app.long_break_due());
+        assert_eq!(app.stats.work_sessions_today(), 6);
+        let streak = app.stats.record_work_session(now);
+        assert_eq!(streak, 1);
+    }
+
+    #[test]
+    fn finished_timer_publishes_a_completion_to_subscribers() {
+        let mut app = app_with_tasks(&["Write report"]);
+        let (events, mut subscriber) = tokio::sync::broadcast::channel(8);
+        app.events = Some(events);
+        let id = app.tasks[0].id;
+        let timer = &mut app.tasks[0].timer;
+        timer.accumulated_time = timer.target_duration;
+        timer.start();
+
+        app.check_and_notify_completions();
+        app.check_and_notify_completions();
+        assert!(matches!(
+            subscriber.try_recv(),
+            Ok(TimerEvent::Completed { id: got }) if got == id
+        ));
+        assert!(subscriber.try_recv().is_err());
+    }
+
+    #[test]
+    fn timer_minutes_overflowing_i64_hit_the_cap() {
+        let huge = "99999999999999999999999";
+        let err = parse_timer_minutes(huge, 1440, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("{} minutes is over the 1440 minute limit", huge)
+        );
+        assert_eq!(parse_timer_minutes(huge, 1440, true).unwrap(), 1440);
+        assert!(parse_timer_minutes("12x", 1440, true).is_err());
+    }
+
+    #[test]
+    fn timer_minutes_above_the_cap_are_rejected_or_clamped() {
+        assert_eq!(parse_timer_minutes("1440", 1440, false).unwrap(), 1440);
+        let err = parse_timer_minutes("1441", 1440, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1441 minutes is over the 1440 minute limit"
+        );
+        assert_eq!(parse_timer_minutes("1441", 1440, true).unwrap(), 1440);
+        assert_eq!(parse_timer_minutes("90", 1440, false).unwrap(), 90);
+    }
+
+    #[test]
+    fn selection_is_restored_by_id_after_a_reorder() {
+        let mut app = app_with_tasks(&["a", "b", "c"]);
+        app.selected_task = 1;
+        app.reconcile_selection();
+        let json = serde_json::to_string(&app).unwrap();
+
+        let mut loaded: App = serde_json::from_str(&json).unwrap();
+        loaded.tasks.reverse();
+        loaded.restore_selection_by_id();
+        assert_eq!(loaded.tasks[loaded.selected_task].description, "b");
+
+        loaded.tasks.retain(|t| t.description != "b");
+        loaded.selected_task = 1;
+        loaded.restore_selection_by_id();
+        assert_eq!(loaded.selected_task, 0);
+    }
+
+    #[test]
+    fn status_summary_shows_today_and_the_streak() {
+        let mut stats = Stats::default();
+        let icons = Icons::default();
+        assert_eq!(stats.status_summary(&icons), "⌾ 0 today  🔥 0d");
+        let now = Local::now();
+        stats.record_completion(now);
+        stats.record_completion(now);
+        stats.record_completion(now - Duration::days(1));
+        stats.daily_streak = 4;
+        assert_eq!(stats.status_summary(&icons), "⌾ 2 today  🔥 4d");
+
+        let plain = Icons {
+            done: "x".to_string(),
+            streak: "streak".to_string(),
+            ..Icons::default()
+        };
+        assert_eq!(stats.status_summary(&plain), "x 2 today  streak 4d");
+    }
+
+    #[test]
+    fn deferred_task_is_hidden_today_and_shown_tomorrow() {
+        let mut app = app_with_tasks(&["a", "b"]);
+        app.defer_selected_task(1);
+        let today = Local::now().date_naive();
+        let task = &app.tasks[0];
+        assert_eq!(task.deferred_until, Some(today + Duration::days(1)));
+        assert!(task.is_deferred_on(today));
+        assert!(!task.is_deferred_on(today + Duration::days(1)));
+        assert!(!app.is_task_visible(0));
+        assert_eq!(app.selected_task, 1);
+
+        app.selected_task = 0;
+        app.defer_selected_task(0);
+        assert_eq!(app.tasks[0].deferred_until, None);
+        assert!(app.is_task_visible(0));
+    }
+
+    #[test]
+    fn finished_timer_completes_its_task_once_when_enabled() {
+        for enabled in [false, true] {
+            let mut app = app_with_tasks(&["Write report"]);
+            app.config.features.complete_on_timer_finish = enabled;
+            let timer = &mut app.tasks[0].timer;
+            timer.accumulated_time = timer.target_duration;
+            timer.start();
+
+            for _ in 0..3 {
+                app.check_and_notify_completions();
+            }
+            assert_eq!(app.tasks[0].completed, enabled);
+            let expected = u32::from(enabled);
+            assert_eq!(app.stats.total_completed, expected);
+            assert_eq!(app.stats.completed_today(), expected);
+        }
+    }
+
+    #[test]
+    fn total_remaining_sums_incomplete_timers() {
+        let mut app = app_with_tasks(&["running", "idle", "done", "overrun"]);
+        for task in &mut app.tasks {
+            task.timer.target_duration = Duration::minutes(25);
+        }
+        app.tasks[0].timer.accumulated_time = Duration::minutes(10);
+        app.tasks[0].timer.start();
+        app.tasks[2].completed = true;
+        app.tasks[3].timer.accumulated_time = Duration::minutes(40);
+
+        let close_to = |total: Duration, minutes: i64| {
+            let gap = Duration::minutes(minutes) - total;
+            gap >= Duration::zero() && gap < Duration::seconds(1)
+        };
+        assert!(close_to(app.total_remaining(), 40));
+        app.config.features.remaining_running_only = true;
+        assert!(close_to(app.total_remaining(), 15));
+    }
+
+    #[test]
+    fn stop_keeps_elapsed_and_reset_clears_it() {
+        let mut timer = timer_in(TimerState::Running);
+        timer.stop_at(at(9, 20));
+        assert_eq!(timer.state, TimerState::Idle);
+        assert_eq!(timer.accumulated_time, Duration::minutes(20));
+
+        timer.reset();
+        assert_eq!(timer.state, TimerState::Idle);
+        assert_eq!(timer.accumulated_time, Duration::zero());
+    }
+
+    #[test]
+    fn bell_rings_once_per_finished_timer() {
+        let mut app = app_with_tasks(&["Write report"]);
+        let timer = &mut app.tasks[0].timer;
+        timer.accumulated_time = timer.target_duration;
+        timer.start();
+        app.check_and_notify_completions();
+        assert!(!app.take_bell());
+
+        app.reset_selected_timer();
+        app.config.features.bell_on_complete = true;
+        let timer = &mut app.tasks[0].timer;
+        timer.accumulated_time = timer.target_duration;
+        timer.start();
+        app.check_and_notify_completions();
+        assert!(app.take_bell());
+        assert!(!app.take_bell());
+        app.check_and_notify_completions();
+        assert!(!app.take_bell());
+    }
+
+    #[test]
+    fn categories_sort_by_name_or_by_count_with_ties_by_name() {
+        let mut stats = Stats::default();
+        for (category, tasks) in [
+            (TaskCategory::Work, 3),
+            (TaskCategory::Study, 5),
+            (TaskCategory::Exercise, 3),
+            (TaskCategory::Personal, 1),
+        ] {
+            stats.tasks_by_category.insert(category, tasks);
+        }
+        stats
+            .timers_completed_by_category
+            .insert(TaskCategory::Other("Reading".to_string()), 2);
+
+        let names = |mode| -> Vec<String> {
+            stats
+                .sorted_categories(mode)
+                .into_iter()
+                .map(|row| row.0)
+                .collect()
+        };
+        assert_eq!(
+            names(CategorySort::Name),
+            ["Exercise", "Personal", "Reading", "Study", "Work"]
+        );
+        assert_eq!(
+            names(CategorySort::Count),
+            ["Study", "Exercise", "Work", "Personal", "Reading"]
+        );
+        let rows = stats.sorted_categories(CategorySort::Name);
+        assert!(rows.contains(&("Reading".to_string(), 0, 2)));
+    }
+
+    #[test]
+    fn duplicates_merge_with_combined_elapsed_and_earliest_creation() {
+        let mut app = app_with_tasks(&["Write report", "Read mail", " write REPORT "]);
+        app.tasks[0].created_at = at(10, 0);
+        app.tasks[0].timer.accumulated_time = Duration::minutes(20);
+        app.tasks[2].created_at = at(9, 0);
+        app.tasks[2].timer.accumulated_time = Duration::minutes(15);
+        let merged_id = app.tasks[2].id;
+        app.notifications_sent.push(merged_id);
+
+        assert_eq!(app.merge_duplicates(), 1);
+        assert_eq!(descriptions(&app), ["Write report", "Read mail"]);
+        let survivor = &app.tasks[0];
+        assert_eq!(survivor.timer.accumulated_time, Duration::minutes(35));
+        assert_eq!(survivor.created_at, at(9, 0));
+        assert!(!app.notifications_sent.contains(&merged_id));
+        assert_eq!(app.merge_duplicates(), 0);
+    }
+
+    #[test]
+    fn weekly_goal_progress_under_at_and_over_the_goal() {
+        let progress = |hours: i64| {
+            let mut app = app_with_tasks(&["Write report"]);
+            let timer = &mut app.tasks[0].timer;
+            // The Sunday before doesn't count toward a week starting Monday.
+            timer.start_at(at(8, 0) - Duration::days(1));
+            timer.pause_at(at(12, 0) - Duration::days(1));
+            timer.resume_at(at(8, 0));
+            timer.pause_at(at(8, 0) + Duration::hours(hours));
+            Stats::weekly_goal_progress(&app.tasks, 10.0, Weekday::Mon, at(23, 0))
+        };
+        assert_eq!(progress(5), 0.5);
+        assert_eq!(progress(10), 1.0);
+        assert_eq!(progress(15), 1.5);
+        let no_goal = Stats::weekly_goal_progress(&[], 0.0, Weekday::Mon, at(23, 0));
+        assert_eq!(no_goal, 0.0);
+    }
+
+    #[test]
+    fn active_tasks_are_running_and_optionally_paused() {
+        let mut app = app_with_tasks(&["idle", "running", "paused", "running"]);
+        app.tasks[1].timer.start_at(at(9, 0));
+        app.tasks[2].timer.start_at(at(9, 0));
+        app.tasks[2].timer.pause_at(at(9, 5));
+        app.tasks[3].timer.start_at(at(9, 0));
+
+        app.config.features.active_includes_paused = false;
+        assert_eq!(app.active_task_indices(), [1, 3]);
+        app.config.features.active_includes_paused = true;
+        assert_eq!(app.active_task_indices(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn frame_animation_stops_after_the_last_frame() {
+        let interval = std::time::Duration::from_millis(100);
+        let start = Instant::now();
+        let mut animation = FrameAnimation::new(start);
+        assert!(animation.advance(start + interval / 2, 3, interval));
+        assert_eq!(animation.frame, 0);
+
+        assert!(animation.advance(start + interval, 3, interval));
+        assert!(animation.advance(start + interval * 2, 3, interval));
+        assert_eq!(animation.frame, 2);
+        assert!(animation.advance(start + interval * 5 / 2, 3, interval));
+        assert!(!animation.advance(start + interval * 3, 3, interval));
+        assert_eq!(animation.frame, 2);
+    }
+
+    #[test]
+    fn done_icon_falls_back_to_the_static_icon() {
+        let mut app = app_with_tasks(&["Write report"]);
+        app.config.icons.done_frames = vec!["◐".to_string(), "✓".to_string()];
+        let id = app.tasks[0].id;
+        let start = Instant::now();
+        app.done_animations.insert(id, FrameAnimation::new(start));
+        assert_eq!(app.done_icon(id), "◐");
+        app.advance_done_animations(start + DONE_FRAME_INTERVAL);
+        assert_eq!(app.done_icon(id), "✓");
+        app.advance_done_animations(start + DONE_FRAME_INTERVAL * 2);
+        assert_eq!(app.done_icon(id), app.config.icons.done);
+    }
+
+    #[test]
+    fn daily_goal_counts_today_and_celebrates_once_per_day() {
+        let mut stats = Stats::default();
+        let now = Local::now();
+        let yesterday = now - Duration::days(1);
+        for _ in 0..5 {
+            stats.record_completion(yesterday);
+        }
+        stats.record_completion(now);
+        stats.record_completion(now);
+        assert_eq!(stats.daily_goal_progress(3), (2, false));
+        stats.record_completion(now);
+        assert_eq!(stats.daily_goal_progress(3), (3, true));
+
+        assert!(stats.claim_goal_celebration(yesterday.date_naive()));
+        assert!(!stats.claim_goal_celebration(yesterday.date_naive()));
+        assert!(stats.claim_goal_celebration(now.date_naive()));
+        assert!(!stats.claim_goal_celebration(now.date_naive()));
+    }
+
+    #[test]
+    fn toast_expires_after_its_duration() {
+        let mut app = test_app();
+        let raised = Instant::now();
+        assert_eq!(app.active_toast(raised), None);
+        app.toast = Some(("✓ Write report (25m 00s)".to_string(), raised));
+        assert_eq!(app.active_toast(raised), Some("✓ Write report (25m 00s)"));
+        let almost = TOAST_DURATION - std::time::Duration::from_millis(1);
+        assert!(app.active_toast(raised + almost).is_some());
+        assert_eq!(app.active_toast(raised + TOAST_DURATION), None);
+    }
+
+    #[test]
+    fn elapsed_time_compares_to_the_average_completed_task() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.average_time_per_completed(), None);
+        assert_eq!(stats.compare_to_average(Duration::minutes(30)), None);
+
+        stats.total_completed = 4;
+        stats.total_time_worked = Duration::minutes(120);
+        assert_eq!(
+            stats.average_time_per_completed(),
+            Some(Duration::minutes(30))
+        );
+        let compare = |minutes| stats.compare_to_average(Duration::minutes(minutes));
+        assert_eq!(compare(20), Some(AverageComparison::Below));
+        assert_eq!(compare(27), Some(AverageComparison::Near));
+        assert_eq!(compare(33), Some(AverageComparison::Near));
+        assert_eq!(compare(40), Some(AverageComparison::Above));
+    }
+
+    #[test]
+    fn startup_animation_ends_in_the_configured_view() {
+        for (view, mode) in [
+            (StartupView::Tasks, AppMode::Normal),
+            (StartupView::Stats, AppMode::ShowStats),
+            (StartupView::Help, AppMode::ShowHelp),
+        ] {
+            let mut app = test_app();
+            app.config.features.startup_view = view;
+            app.config.features.effects_enabled = Some(true);
+            app.mode = AppMode::StartupAnimation;
+            app.trigger_startup_animation();
+            let ends_at = app.startup_ends_at.unwrap();
+
+            app.finish_startup_at(ends_at - std::time::Duration::from_millis(1));
+            assert_eq!(app.mode, AppMode::StartupAnimation);
+            app.finish_startup_at(ends_at);
+            assert_eq!(app.mode, mode);
+        }
+    }
+
+    #[test]
+    fn finished_timer_counts_once_for_its_category() {
+        let mut app = app_with_tasks(&["Write report", "Run"]);
+        app.tasks[0].category = TaskCategory::Work;
+        app.tasks[1].category = TaskCategory::Exercise;
+        let timer = &mut app.tasks[0].timer;
+        timer.accumulated_time = timer.target_duration;
+        timer.start();
+
+        for _ in 0..3 {
+            app.check_and_notify_completions();
+        }
+        let counts = &app.stats.timers_completed_by_category;
+        assert_eq!(counts.get(&TaskCategory::Work), Some(&1));
+        assert_eq!(counts.get(&TaskCategory::Exercise), None);
+        assert!(!app.tasks[0].completed);
+    }
+
+    #[test]
+    fn longer_focus_session_beats_the_record() {
+        let mut stats = Stats::default();
+        stats.record_focus_session(at(9, 0), Duration::minutes(30));
+        stats.record_focus_session(at(10, 0), Duration::minutes(40));
+        stats.record_focus_session(at(11, 0), Duration::minutes(20));
+        assert_eq!(stats.longest_focus_session, Duration::minutes(40));
+        let day = &stats.daily[&at(9, 0).date_naive()];
+        assert_eq!(day.longest_focus_session, Duration::minutes(40));
+    }
+
+    #[test]
+    fn pausing_a_task_records_its_run() {
+        let mut app = app_with_tasks(&["Write report"]);
+        app.stats.longest_focus_session = Duration::minutes(30);
+        let started = Local::now() - Duration::minutes(40);
+        app.tasks[0].timer.start_at(started);
+        app.check_and_notify_completions();
+        assert_eq!(app.stats.longest_focus_session, Duration::minutes(30));
+
+        app.toggle_selected_timer();
+        assert_eq!(app.tasks[0].timer.state, TimerState::Paused);
+        assert!(app.stats.longest_focus_session >= Duration::minutes(40));
+        let day = &app.stats.daily[&started.date_naive()];
+        assert!(day.longest_focus_session >= Duration::minutes(40));
+    }
+
+    #[test]
+    fn category_cycles_through_the_built_ins_and_wraps() {
+        let mut app = app_with_tasks(&["Write report"]);
+        app.tasks[0].category = TaskCategory::Other("Reading".to_string());
+        let mut seen = vec![];
+        for _ in 0..6 {
+            app.cycle_selected_task_category();
+            seen.push(app.tasks[0].category.to_string());
+        }
+        assert_eq!(
+            seen,
+            ["Work", "Personal", "Study", "Exercise", "General", "Work"]
+        );
+    }
+
+    #[test]
+    fn out_of_range_selection_does_not_panic() {
+        let mut app = app_with_tasks(&["a", "b"]);
+        app.selected_task = 5;
+        app.toggle_selected_task_completion();
+        assert_eq!(app.selected_task, 1);
+        assert!(app.tasks.iter().all(|t| !t.completed));
+
+        app.selected_task = 5;
+        app.delete_selected_task();
+        assert_eq!(app.tasks.len(), 2);
+        app.tasks.clear();
+        app.selected_task = 3;
+        app.toggle_selected_task_completion();
+        assert_eq!(app.selected_task, 0);
+    }
+
+    #[test]
+    fn effects_use_the_configured_durations() {
+        use tachyonfx::Shader;
+
+        let mut app = test_app();
+        app.config = toml::from_str("[effects]\ndelete = 120\ncomplete = 40\n").unwrap();
+        let ms = |effect: Effect| effect.timer().unwrap().duration().as_millis();
+        assert_eq!(ms(app.delete_effect()), 120);
+        assert_eq!(ms(app.complete_effect()), 40);
+        assert_eq!(ms(test_app().delete_effect()), 500);
+    }
+
+    #[test]
+    fn split_task_adds_subtasks_and_divides_the_duration() {
+        let mut app = app_with_tasks(&["write report"]);
+        app.set_task_duration(0, 50);
+        let lines = vec!["outline".into(), "  ".into(), "draft".into(), "edit".into()];
+        assert_eq!(app.split_task(0, lines, true), 3);
+        let subtasks = &app.tasks[0].subtasks;
+        let names: Vec<_> = subtasks.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(names, ["outline", "draft", "edit"]);
+        let minutes: Vec<_> = subtasks.iter().map(|s| s.target_minutes).collect();
+        assert_eq!(minutes, [Some(17), Some(17), Some(16)]);
+        assert!(subtasks.iter().all(|s| !s.completed));
+        assert_eq!(app.tasks[0].timer.target_duration, Duration::minutes(50));
+
+        app.split_task(0, vec!["review".into()], false);
+        assert_eq!(app.tasks[0].subtasks[3].target_minutes, None);
+        assert_eq!(app.split_task(4, vec!["nothing".into()], true), 0);
+    }
+
+    #[test]
+    fn cancelling_a_split_drops_the_entered_lines() {
+        let mut app = app_with_tasks(&["write report"]);
+        app.start_split_selected_task();
+        for c in "outline\ndra".chars() {
+            app.handle_char(c);
+        }
+        app.handle_backspace();
+        assert_eq!(app.input_buffer, "dr");
+        app.cancel_split_task();
+        assert!(app.split_lines.is_empty() && app.input_buffer.is_empty());
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.tasks[0].subtasks.is_empty());
+    }
+
+    #[test]
+    fn idle_quit_needs_no_activity_and_no_running_timer() {
+        let mut app = app_with_tasks(&["a"]);
+        let start = Instant::now();
+        let later = |minutes: u64| start + std::time::Duration::from_secs(minutes * 60);
+        assert!(!app.idle_quit_due(later(600)));
+
+        app.config.features.auto_quit_after_idle = Some(10);
+        app.note_activity(start);
+        assert!(!app.idle_quit_due(later(9)));
+        assert!(app.idle_quit_due(later(10)));
+
+        app.tasks[0].timer.start();
+        assert!(!app.idle_quit_due(later(30)));
+        app.tasks[0].timer.pause();
+        assert!(!app.idle_quit_due(later(39)));
+        assert!(app.idle_quit_due(later(40)));
+
+        app.global_timer.start();
+        assert!(!app.idle_quit_due(later(60)));
+    }
+
+    #[test]
+    fn burndown_falls_with_completions_and_rises_with_additions() {
+        let mut app = test_app();
+        let yesterday = at(9, 0) - Duration::days(1);
+        let mut planned = |created, completed, minutes| {
+            let mut task = task_at(&mut app, created, completed);
+            task.timer.target_duration = Duration::minutes(minutes);
+            task
+        };
+        let tasks = vec![
+            planned(yesterday, Some(at(10, 0)), 30),
+            planned(yesterday, Some(at(14, 0)), 20),
+            planned(yesterday, None, 25),
+            planned(at(12, 0), None, 50),
+            planned(yesterday, Some(yesterday), 45),
+        ];
+
+        let points = Stats::burndown_points(&tasks, at(0, 0).date_naive(), at(16, 0));
+        let expected = [
+            (at(0, 0), 75),
+            (at(10, 0), 45),
+            (at(12, 0), 95),
+            (at(14, 0), 75),
+            (at(16, 0), 75),
+        ];
+        assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn zero_target_timer_is_open_ended() {
+        let mut timer = Timer::new(0);
+        timer.accumulated_time = Duration::minutes(10);
+        assert!(timer.is_open_ended());
+        assert!(!timer.is_complete());
+        assert_eq!(timer.get_progress(), 0.0);
+        assert_eq!(timer.shown_time(), (Duration::minutes(10), true));
+
+        timer.target_duration = Duration::minutes(-5);
+        assert!(timer.is_open_ended());
+        assert!(!timer.is_complete());
+
+        timer.target_duration = Duration::minutes(20);
+        assert!(!timer.is_open_ended());
+        assert_eq!(timer.get_progress(), 0.5);
+        assert_eq!(timer.shown_time(), (Duration::minutes(10), false));
+    }
+
+    #[test]
+    fn toggle_subtask_ignores_missing_indices() {
+        let mut app = app_with_tasks(&["write report"]);
+        app.split_task(0, vec!["outline".into()], false);
+        assert_eq!(app.toggle_subtask(0, 0), Some(true));
+        assert_eq!(app.toggle_subtask(0, 0), Some(false));
+        assert_eq!(app.toggle_subtask(0, 1), None);
+        assert_eq!(app.toggle_subtask(1, 0), None);
+    }
+
+    #[test]
+    fn suggestion_prefers_priority_over_age() {
+        let mut app = app_with_tasks(&["old low", "new high", "done urgent"]);
+        app.tasks[0].priority = Priority::Low;
+        app.tasks[1].priority = Priority::High;
+        app.tasks[2].priority = Priority::Urgent;
+        app.tasks[2].completed = true;
+        app.tasks[1].created_at = app.tasks[0].created_at + Duration::hours(1);
+        assert_eq!(app.suggest_next_task(), Some(1));
+
+        app.tasks[1].deferred_until = Some(Local::now().date_naive() + Duration::days(1));
+        assert_eq!(app.suggest_next_task(), Some(0));
+
+        for task in &mut app.tasks {
+            task.completed = true;
+        }
+        assert_eq!(app.suggest_next_task(), None);
+    }
+
+    #[test]
+    fn suggestion_breaks_priority_ties_by_creation() {
+        let mut app = app_with_tasks(&["newer", "older", "oldest but low"]);
+        let start = app.tasks[0].created_at;
+        app.tasks[0].created_at = start + Duration::hours(2);
+        app.tasks[1].created_at = start + Duration::hours(1);
+        app.tasks[2].created_at = start;
+        app.tasks[2].priority = Priority::Low;
+        assert_eq!(app.suggest_next_task(), Some(1));
+
+        app.select_suggested_task();
+        assert_eq!(app.selected_task, 1);
+        assert_eq!(app.status_message.as_deref(), Some("Suggested: older"));
+    }
+
+    #[test]
+    fn sticky_add_stays_open_until_an_empty_confirm() {
+        let mut app = test_app();
+        app.config.features.sticky_add = true;
+        app.mode = AppMode::AddingTask;
+        for c in "first\nsecond\n".chars() {
+            app.handle_char(c);
+        }
+        assert_eq!(app.mode, AppMode::AddingTask);
+        assert!(app.input_buffer.is_empty());
+        assert_eq!(descriptions(&app), ["first", "second"]);
+        app.handle_char('\n');
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.tasks.len(), 2);
+
+        app.config.features.sticky_add = false;
+        app.mode = AppMode::AddingTask;
+        for c in "third\n".chars() {
+            app.handle_char(c);
+        }
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn renaming_can_be_undone_and_redone() {
+        let mut app = app_with_tasks(&["Write reprot"]);
+        app.start_rename_selected_task();
+        assert_eq!(app.mode, AppMode::RenamingTask(0));
+        assert_eq!(app.input_buffer, "Write reprot");
+        for _ in 0.."reprot".len() {
+            app.handle_backspace();
+        }
+        for c in "report\n".chars() {
+            app.handle_char(c);
+        }
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(descriptions(&app), ["Write report"]);
+
+        app.undo();
+        assert_eq!(descriptions(&app), ["Write reprot"]);
+        assert_eq!(app.status_message.as_deref(), Some("Undid rename"));
+        app.redo();
+        assert_eq!(descriptions(&app), ["Write report"]);
+        assert_eq!(app.status_message.as_deref(), Some("Redid rename"));
+
+        app.rename_task(0, "   ");
+        app.rename_task(0, "Write report");
+        app.undo();
+        assert_eq!(descriptions(&app), ["Write reprot"]);
+        app.undo();
+        assert_eq!(app.status_message.as_deref(), Some("Nothing to undo"));
+    }
+
+    #[test]
+    fn category_change_can_be_undone_and_redone() {
+        let mut app = app_with_tasks(&["a"]);
+        let before = app.tasks[0].category.clone();
+        app.cycle_selected_task_category();
+        let after = app.tasks[0].category.clone();
+        assert_ne!(before, after);
+
+        app.undo();
+        assert_eq!(app.tasks[0].category, before);
+        assert_eq!(app.status_message.as_deref(), Some("Undid category change"));
+        app.redo();
+        assert_eq!(app.tasks[0].category, after);
+        app.redo();
+        assert_eq!(app.status_message.as_deref(), Some("Nothing to redo"));
+
+        app.undo();
+        app.set_task_category(0, TaskCategory::Study);
+        app.redo();
+        assert_eq!(app.tasks[0].category, TaskCategory::Study);
+        assert_eq!(app.status_message.as_deref(), Some("Nothing to redo"));
+    }
+
+    #[test]
+    fn snoozing_a_paused_task_publishes_its_start() {
+        let mut app = app_with_tasks(&["Write report"]);
+        let (events, mut subscriber) = tokio::sync::broadcast::channel(8);
+        app.events = Some(events);
+        let id = app.tasks[0].id;
+
+        app.snooze_selected_timer(5);
+        assert_eq!(app.tasks[0].timer.state, TimerState::Running);
+        assert_eq!(app.tasks[0].timer.target_duration, Duration::minutes(30));
+        assert!(matches!(
+            subscriber.try_recv(),
+            Ok(TimerEvent::Started { id: got }) if got == id
+        ));
+
+        app.snooze_selected_timer(5);
+        assert!(subscriber.try_recv().is_err());
+    }
+
+    #[test]
+    fn running_task_absorbs_a_later_duplicate_and_keeps_running() {
+        let mut app = app_with_tasks(&["Write report", "Write report"]);
+        app.tasks[0].timer.start_at(at(9, 30));
+        app.tasks[0].timer.laps.push(Duration::minutes(3));
+        app.tasks[0].estimated_pomodoros = 2;
+        app.split_task(0, vec!["outline".into()], false);
+        let duplicate = &mut app.tasks[1];
+        duplicate.timer.start_at(at(9, 40));
+        duplicate.timer.pause_at(at(9, 50));
+        duplicate.timer.laps.push(Duration::minutes(4));
+        duplicate.estimated_pomodoros = 1;
+        app.split_task(1, vec!["outline".into(), "proofread".into()], false);
+
+        assert_eq!(app.merge_duplicates(), 1);
+        let task = &app.tasks[0];
+        assert_eq!(task.timer.state, TimerState::Running);
+        let last = task.timer.intervals.last().unwrap();
+        assert_eq!((last.start, last.end), (at(9, 30), None));
+        assert_eq!(task.timer.intervals[0].start, at(9, 40));
+        assert_eq!(
+            task.timer.laps,
+            [Duration::minutes(3), Duration::minutes(4)]
+        );
+        assert_eq!(task.estimated_pomodoros, 3);
+        let subtasks: Vec<_> = task.subtasks.iter().map(|s| &s.description).collect();
+        assert_eq!(subtasks, ["outline", "proofread"]);
+
+        app.tasks[0].timer.pause_at(at(10, 0));
+        let timer = &app.tasks[0].timer;
+        assert!(timer.intervals.iter().all(|i| i.end.is_some()));
+        assert_eq!(timer.intervals.last().unwrap().end, Some(at(10, 0)));
+    }
+
+    #[test]
+    fn completing_a_running_task_ends_its_run() {
+        let mut app = app_with_tasks(&["Write report"]);
+        let started = Local::now() - Duration::minutes(40);
+        app.tasks[0].timer.start_at(started);
+
+        app.toggle_selected_task_completion();
+        let timer = &app.tasks[0].timer;
+        assert!(app.tasks[0].completed);
+        assert_eq!(timer.state, TimerState::Paused);
+        assert!(timer.intervals.last().unwrap().end.is_some());
+        assert!(app.stats.longest_focus_session >= Duration::minutes(40));
+        let day = &app.stats.daily[&started.date_naive()];
+        assert!(day.longest_focus_session >= Duration::minutes(40));
+    }
+
+    #[test]
+    fn suggestion_breaks_priority_ties_by_nearest_due_date() {
+        let mut app = app_with_tasks(&["undated", "due friday", "due tuesday", "low, due today"]);
+        let start = app.tasks[0].created_at;
+        for (i, task) in app.tasks.iter_mut().enumerate() {
+            task.created_at = start + Duration::hours(i as i64);
+        }
+        let monday = at(0, 0).date_naive();
+        app.tasks[1].due = Some(monday + Duration::days(4));
+        app.tasks[2].due = Some(monday + Duration::days(1));
+        app.tasks[3].due = Some(monday);
+        app.tasks[3].priority = Priority::Low;
+        assert_eq!(app.suggest_next_task(), Some(2));
+
+        app.tasks[2].completed = true;
+        assert_eq!(app.suggest_next_task(), Some(1));
+        app.tasks[1].due = None;
+        assert_eq!(app.suggest_next_task(), Some(0));
+    }
+
+    #[test]
+    fn due_date_is_parsed_or_cleared() {
+        let mut app = app_with_tasks(&["Write report"]);
+        app.start_edit_due_selected_task();
+        assert_eq!(app.mode, AppMode::EditingDue(0));
+        for c in "2026-03-06\n".chars() {
+            app.handle_char(c);
+        }
+        assert_eq!(app.tasks[0].due, NaiveDate::from_ymd_opt(2026, 3, 6));
+        assert_eq!(app.mode, AppMode::Normal);
+
+        app.start_edit_due_selected_task();
+        assert_eq!(app.input_buffer, "2026-03-06");
+        let err = app.set_task_due(0, "friday").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid due date \"friday\", use YYYY-MM-DD"
+        );
+        assert!(app.tasks[0].due.is_some());
+        app.set_task_due(0, " ").unwrap();
+        assert_eq!(app.tasks[0].due, None);
     }
 }
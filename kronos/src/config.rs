@@ -1,15 +1,19 @@
+use crate::persistence::Persistence;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveTime, Weekday};
 use directories::ProjectDirs;
-use ratatui::style::Color;
+use ratatui::{style::Color, widgets::BorderType};
 use serde::Deserialize;
-use std::fs;
+use std::{fs, path::PathBuf};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 #[serde(default)]
 pub struct Config {
     pub theme: Theme,
     pub icons: Icons,
     pub features: Features,
+    pub borders: BorderStyleConfig,
+    pub effects: EffectDurations,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -17,9 +21,95 @@ pub struct Config {
 pub struct Features {
     pub auto_save_interval: u64,
     pub show_seconds: bool,
+    /// With seconds hidden, show timers of an hour or more as `1h 30m`
+    /// rather than `90m`.
+    pub show_hours: bool,
     pub notification_sound: bool,
+    /// Ring the terminal bell when a timer completes; works over SSH where
+    /// desktop notifications don't.
+    pub bell_on_complete: bool,
+    /// Local time window, e.g. `["22:00", "07:00"]`, in which desktop
+    /// notifications are suppressed. May wrap past midnight.
+    pub quiet_hours: Option<(NaiveTime, NaiveTime)>,
     pub break_reminders: bool,
+    /// Continuous task-timer work after which a break is suggested.
+    pub break_after_minutes: i64,
+    /// Time with no task timer running that counts as having taken a break.
+    pub break_idle_minutes: i64,
+    /// Finished task timers in a day after which a long break is suggested.
+    pub long_break_after_sessions: u32,
+    /// Time with no task timer running that counts as the long break.
+    pub long_break_minutes: i64,
     pub daily_stats: bool,
+    /// Show today's total focused time under the global timer.
+    pub show_today_total: bool,
+    /// Show tasks completed today and the streak at the right of the status bar.
+    pub status_bar_stats: bool,
+    /// Task list width below which rows switch to the compact layout.
+    pub compact_width: u16,
+    /// Append completed tasks to a daily plaintext worklog.
+    pub worklog: bool,
+    /// Directory for worklog files; defaults to `worklog/` under the data dir.
+    pub worklog_dir: Option<String>,
+    pub snooze_minutes: i64,
+    /// Keep a task's elapsed time when switching it to another preset instead
+    /// of resetting its timer.
+    pub keep_elapsed_on_preset: bool,
+    /// Play tachyonfx animations (startup sweep, completion flash, ...).
+    /// Unset follows the environment, see `reduced_motion_requested`.
+    pub effects_enabled: Option<bool>,
+    /// Start the next incomplete task's timer when one finishes.
+    pub auto_advance: bool,
+    /// Treat the global timer as a one-shot alarm that pauses when it reaches zero.
+    pub global_alarm: bool,
+    /// Show the running task's remaining time and progress in the terminal title.
+    pub set_terminal_title: bool,
+    /// Show wall-clock times as 24-hour ("14:05") rather than 12-hour ("02:05 PM").
+    pub time_format_24h: bool,
+    /// Progress (0.0-1.0) at which timer bars turn from green to yellow.
+    pub progress_warn_at: f64,
+    /// Refuse to add tasks once the list holds this many.
+    pub max_tasks: Option<usize>,
+    /// Append " (copy)" to the description of duplicated tasks.
+    pub duplicate_suffix: bool,
+    /// Largest size, in cells, that overlays grow to on big terminals.
+    pub overlay_max_width: u16,
+    pub overlay_max_height: u16,
+    /// Longest task timer, in minutes, that can be typed in.
+    pub max_timer_minutes: i64,
+    /// Clamp longer entries to `max_timer_minutes` instead of rejecting them.
+    pub clamp_timer_minutes: bool,
+    /// Mark a task done when its timer runs out.
+    pub complete_on_timer_finish: bool,
+    /// Count only running timers in the header's time-left total.
+    pub remaining_running_only: bool,
+    /// Shell command run when a task timer starts; see `hooks` for the
+    /// placeholders.
+    pub on_start_command: Option<String>,
+    /// Shell command run when a task is completed.
+    pub on_complete_command: Option<String>,
+    /// Row order of the stats overlay's category table.
+    pub category_sort: CategorySort,
+    /// Focus hours to aim for each week, shown as a bar in the stats overlay.
+    pub weekly_goal_hours: Option<f64>,
+    /// First day of the week for the weekly goal, e.g. `"Mon"` or `"Sun"`.
+    pub week_start: Weekday,
+    /// Count paused timers as active for the only-active filter.
+    pub active_includes_paused: bool,
+    /// Tasks to complete each day; the header shows progress as `3/5`.
+    pub daily_task_goal: Option<u32>,
+    /// What to show once the startup animation ends.
+    pub startup_view: StartupView,
+    /// Show the global timer pane and enable its keys (`g`, `G`, `T`).
+    pub show_global_timer: bool,
+    /// Share a split task's timer length out among its new subtasks.
+    pub split_divides_duration: bool,
+    /// Keep the add-task input open after each task, for entering several
+    /// in a row. An empty line or Esc closes it.
+    pub sticky_add: bool,
+    /// Quit once this many minutes pass with no key press, no IPC command
+    /// and no running timer, so a forgotten instance doesn't linger.
+    pub auto_quit_after_idle: Option<u64>,
 }
 
 impl Default for Features {
@@ -27,13 +117,85 @@ impl Default for Features {
         Self {
             auto_save_interval: 5,
             show_seconds: true,
+            show_hours: true,
             notification_sound: true,
+            bell_on_complete: false,
+            quiet_hours: None,
             break_reminders: true,
+            break_after_minutes: 60,
+            break_idle_minutes: 5,
+            long_break_after_sessions: 4,
+            long_break_minutes: 15,
             daily_stats: true,
+            show_today_total: true,
+            status_bar_stats: true,
+            compact_width: 60,
+            worklog: false,
+            worklog_dir: None,
+            snooze_minutes: 5,
+            keep_elapsed_on_preset: false,
+            effects_enabled: None,
+            auto_advance: false,
+            global_alarm: false,
+            set_terminal_title: true,
+            time_format_24h: true,
+            progress_warn_at: 0.8,
+            max_tasks: None,
+            duplicate_suffix: true,
+            overlay_max_width: 80,
+            overlay_max_height: 20,
+            max_timer_minutes: 1440,
+            clamp_timer_minutes: false,
+            complete_on_timer_finish: false,
+            remaining_running_only: false,
+            on_start_command: None,
+            on_complete_command: None,
+            category_sort: CategorySort::Name,
+            weekly_goal_hours: None,
+            week_start: Weekday::Mon,
+            active_includes_paused: false,
+            daily_task_goal: None,
+            startup_view: StartupView::Tasks,
+            show_global_timer: true,
+            split_divides_duration: false,
+            sticky_add: false,
+            auto_quit_after_idle: None,
         }
     }
 }
 
+impl Features {
+    /// Whether `now` falls inside `quiet_hours`.
+    pub fn in_quiet_hours(&self, now: NaiveTime) -> bool {
+        match self.quiet_hours {
+            Some((start, end)) if start <= end => start <= now && now < end,
+            Some((start, end)) => now >= start || now < end,
+            None => false,
+        }
+    }
+
+    /// Switches between MM:SS and MM timer displays, returning the new value.
+    pub fn toggle_show_seconds(&mut self) -> bool {
+        self.show_seconds = !self.show_seconds;
+        self.show_seconds
+    }
+
+    /// Wall-clock time of day, honoring `time_format_24h`.
+    pub fn format_time(&self, at: DateTime<Local>) -> String {
+        let format = if self.time_format_24h {
+            "%H:%M"
+        } else {
+            "%I:%M %p"
+        };
+        at.format(format).to_string()
+    }
+
+    /// Date plus wall-clock time, honoring `time_format_24h`.
+    pub fn format_datetime(&self, at: DateTime<Local>) -> String {
+        format!("{} {}", at.format("%Y-%m-%d"), self.format_time(at))
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(default)]
 pub struct Theme {
@@ -61,6 +223,82 @@ pub struct Theme {
     pub gray: Color,
 }
 
+/// How category rows are ordered: `name` (alphabetical) or `count` (most
+/// tasks first, ties by name).
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CategorySort {
+    #[default]
+    Name,
+    Count,
+}
+
+/// Screen Kronos opens on: `tasks`, `stats` or `help`.
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StartupView {
+    #[default]
+    Tasks,
+    Stats,
+    Help,
+}
+
+/// Border types for the main panes and the pop-up overlays. Each is one of
+/// `plain`, `rounded`, `double` or `thick`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct BorderStyleConfig {
+    #[serde(deserialize_with = "name_to_border_type")]
+    pub panes: BorderType,
+    #[serde(deserialize_with = "name_to_border_type")]
+    pub overlays: BorderType,
+}
+
+impl Default for BorderStyleConfig {
+    fn default() -> Self {
+        Self {
+            panes: BorderType::Rounded,
+            overlays: BorderType::Double,
+        }
+    }
+}
+
+/// Length of each animation in milliseconds, from 10 to 10000.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct EffectDurations {
+    #[serde(deserialize_with = "effect_ms")]
+    pub startup: u32,
+    #[serde(deserialize_with = "effect_ms")]
+    pub mode_change: u32,
+    #[serde(deserialize_with = "effect_ms")]
+    pub delete: u32,
+    #[serde(deserialize_with = "effect_ms")]
+    pub complete: u32,
+    /// The green fade on a completed task.
+    #[serde(deserialize_with = "effect_ms")]
+    pub celebration: u32,
+    /// One half of the running timer's pulse.
+    #[serde(deserialize_with = "effect_ms")]
+    pub running_pulse: u32,
+    #[serde(deserialize_with = "effect_ms")]
+    pub streak: u32,
+}
+
+impl Default for EffectDurations {
+    fn default() -> Self {
+        Self {
+            startup: 800,
+            mode_change: 300,
+            delete: 500,
+            complete: 250,
+            celebration: 500,
+            running_pulse: 400,
+            streak: 2000,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(default)]
 pub struct Icons {
@@ -71,6 +309,9 @@ pub struct Icons {
     pub stop: String,
     pub pending: String,
     pub done: String,
+    /// Played once in place of `done` when a task is completed, e.g.
+    /// `["◜", "◝", "◞", "◟", "✓"]`. Empty disables the animation.
+    pub done_frames: Vec<String>,
     pub select: String,
     pub progress_filled: String,
     pub progress_empty: String,
@@ -78,18 +319,93 @@ pub struct Icons {
     pub separator: String,
     pub header_left: String,
     pub header_right: String,
+    /// Before the daily streak in the header and status bar.
+    pub streak: String,
+    /// Before the time left on today's tasks in the header.
+    pub time_left: String,
+    /// After a task's Pomodoro count.
+    pub pomodoro: String,
+    /// Before the date a deferred task comes back.
+    pub deferred: String,
+    /// Status bar hints for quiet hours, a due break and a due long break.
+    pub quiet: String,
+    pub break_due: String,
+    pub long_break_due: String,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            theme: Theme::default(),
-            icons: Icons::default(),
-            features: Features::default(),
+impl Theme {
+    /// Names of the colors, as written in the `[theme]` table.
+    pub const FIELDS: [&'static str; 11] = [
+        "background",
+        "foreground",
+        "selection",
+        "black",
+        "red",
+        "green",
+        "yellow",
+        "blue",
+        "magenta",
+        "cyan",
+        "gray",
+    ];
+
+    /// All colors, in `FIELDS` order.
+    pub fn colors(&self) -> [Color; 11] {
+        [
+            self.background,
+            self.foreground,
+            self.selection,
+            self.black,
+            self.red,
+            self.green,
+            self.yellow,
+            self.blue,
+            self.magenta,
+            self.cyan,
+            self.gray,
+        ]
+    }
+
+    /// The color at `field` in `FIELDS` order.
+    pub fn color_mut(&mut self, field: usize) -> Option<&mut Color> {
+        match field {
+            0 => Some(&mut self.background),
+            1 => Some(&mut self.foreground),
+            2 => Some(&mut self.selection),
+            3 => Some(&mut self.black),
+            4 => Some(&mut self.red),
+            5 => Some(&mut self.green),
+            6 => Some(&mut self.yellow),
+            7 => Some(&mut self.blue),
+            8 => Some(&mut self.magenta),
+            9 => Some(&mut self.cyan),
+            10 => Some(&mut self.gray),
+            _ => None,
         }
     }
 }
 
+/// Adds `delta` to one channel (0 = R, 1 = G, 2 = B) of an RGB color,
+/// clamping at 0 and 255. Other colors are returned unchanged.
+pub fn nudge_channel(color: Color, channel: usize, delta: i16) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let mut rgb = [r, g, b];
+    if let Some(value) = rgb.get_mut(channel) {
+        *value = (i16::from(*value) + delta).clamp(0, 255) as u8;
+    }
+    Color::Rgb(rgb[0], rgb[1], rgb[2])
+}
+
+/// The `#rrggbb` form `hex_to_color` reads, for RGB colors.
+pub fn color_to_hex(color: Color) -> Option<String> {
+    match color {
+        Color::Rgb(r, g, b) => Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        _ => None,
+    }
+}
+
 impl Default for Theme {
     fn default() -> Self {
         Self {
@@ -118,6 +434,7 @@ impl Default for Icons {
             stop: "■".to_string(),
             pending: "○".to_string(),
             done: "⌾".to_string(),
+            done_frames: Vec::new(),
             select: "▸".to_string(),
             progress_filled: "█".to_string(),
             progress_empty: "░".to_string(),
@@ -125,6 +442,13 @@ impl Default for Icons {
             separator: "│".to_string(),
             header_left: "⟪ ".to_string(),
             header_right: " ⟫".to_string(),
+            streak: "🔥".to_string(),
+            time_left: "⏳".to_string(),
+            pomodoro: "🍅".to_string(),
+            deferred: "⏭".to_string(),
+            quiet: "🔕".to_string(),
+            break_due: "☕".to_string(),
+            long_break_due: "🌴".to_string(),
         }
     }
 }
@@ -143,19 +467,261 @@ where
     Ok(Color::Rgb(r, g, b))
 }
 
+fn name_to_border_type<'de, D>(deserializer: D) -> Result<BorderType, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: String = serde::Deserialize::deserialize(deserializer)?;
+    match s.to_lowercase().as_str() {
+        "plain" => Ok(BorderType::Plain),
+        "rounded" => Ok(BorderType::Rounded),
+        "double" => Ok(BorderType::Double),
+        "thick" => Ok(BorderType::Thick),
+        _ => Err(serde::de::Error::custom(format!(
+            "unknown border type {:?}, expected plain, rounded, double or thick",
+            s
+        ))),
+    }
+}
+
+fn effect_ms<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let ms: u32 = serde::Deserialize::deserialize(deserializer)?;
+    if !(10..=10_000).contains(&ms) {
+        return Err(serde::de::Error::custom(format!(
+            "effect duration {}ms is out of range, expected 10 to 10000",
+            ms
+        )));
+    }
+    Ok(ms)
+}
+
+/// Whether the environment asks for less motion: `NO_MOTION` or
+/// `KRONOS_REDUCED_MOTION` set to anything but empty or `0`.
+pub fn reduced_motion_requested() -> bool {
+    ["NO_MOTION", "KRONOS_REDUCED_MOTION"]
+        .iter()
+        .any(|name| std::env::var(name).is_ok_and(|value| !value.is_empty() && value != "0"))
+}
+
+/// Whether animations play given the `effects_enabled` setting and whether
+/// reduced motion was requested. An explicit setting always wins.
+pub fn effects_active_from(setting: Option<bool>, reduced_motion: bool) -> bool {
+    setting.unwrap_or(!reduced_motion)
+}
+
+/// Location of `kronos.toml`. With a data dir override, or without
+/// platform dirs, it lives next to the state in the data dir.
+fn config_path() -> Option<PathBuf> {
+    let platform_dir = ProjectDirs::from("com", "pabloagn", "Kronos")
+        .map(|proj_dirs| proj_dirs.config_dir().to_path_buf());
+    config_path_for(Persistence::data_dir_override(), platform_dir)
+}
+
+/// `config_path` given the data dir override and the platform config dir.
+fn config_path_for(
+    data_dir_override: Option<PathBuf>,
+    platform_dir: Option<PathBuf>,
+) -> Option<PathBuf> {
+    data_dir_override
+        .or(platform_dir)
+        .or_else(|| Persistence::data_dir().ok())
+        .map(|dir| dir.join("kronos.toml"))
+}
+
 pub fn load_config() -> Result<Config> {
-    match ProjectDirs::from("com", "pabloagn", "Kronos") {
-        Some(proj_dirs) => {
-            let path = proj_dirs.config_dir().join("kronos.toml");
-            if path.exists() {
-                let config_str = fs::read_to_string(&path)
-                    .with_context(|| format!("Failed to read config file at {:?}", path))?;
-                toml::from_str(&config_str)
-                    .with_context(|| format!("Failed to parse config file at {:?}", path))
-            } else {
-                Ok(Config::default())
-            }
+    let Some(path) = config_path() else {
+        return Ok(Config::default());
+    };
+    if path.exists() {
+        let config_str = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file at {:?}", path))?;
+        toml::from_str(&config_str)
+            .with_context(|| format!("Failed to parse config file at {:?}", path))
+    } else {
+        Ok(Config::default())
+    }
+}
+
+/// Writes `theme` into the `[theme]` table of the config file, keeping the
+/// other settings (comments are not preserved). Returns the file written.
+pub fn save_theme(theme: &Theme) -> Result<PathBuf> {
+    let mut table = toml::Table::new();
+    for (name, color) in Theme::FIELDS.iter().zip(theme.colors()) {
+        if let Some(hex) = color_to_hex(color) {
+            table.insert(name.to_string(), toml::Value::String(hex));
+        }
+    }
+    update_config_file(|doc| {
+        doc.insert("theme".to_string(), toml::Value::Table(table));
+    })
+}
+
+/// Sets a single key in the `[features]` table of the config file.
+pub fn save_feature(key: &str, value: toml::Value) -> Result<PathBuf> {
+    update_config_file(|doc| {
+        if let toml::Value::Table(features) = doc
+            .entry("features")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        {
+            features.insert(key.to_string(), value);
+        }
+    })
+}
+
+/// Loads the config file as a plain table, lets `update` change it and
+/// writes it back.
+fn update_config_file(update: impl FnOnce(&mut toml::Table)) -> Result<PathBuf> {
+    let path = config_path().ok_or_else(|| anyhow::anyhow!("No config directory available"))?;
+    let mut doc: toml::Table = if path.exists() {
+        let config_str = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file at {:?}", path))?;
+        toml::from_str(&config_str)
+            .with_context(|| format!("Failed to parse config file at {:?}", path))?
+    } else {
+        toml::Table::new()
+    };
+    update(&mut doc);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, toml::to_string_pretty(&doc)?)
+        .with_context(|| format!("Failed to write config file at {:?}", path))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn afternoon() -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 3, 2, 14, 5, 0)
+            .single()
+            .unwrap()
+    }
+
+    #[test]
+    fn times_follow_the_24h_setting() {
+        let mut features = Features::default();
+        assert_eq!(features.format_time(afternoon()), "14:05");
+        assert_eq!(features.format_datetime(afternoon()), "2026-03-02 14:05");
+        features.time_format_24h = false;
+        assert_eq!(features.format_time(afternoon()), "02:05 PM");
+        assert_eq!(features.format_datetime(afternoon()), "2026-03-02 02:05 PM");
+    }
+
+    fn time(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let mut features = Features::default();
+        assert!(!features.in_quiet_hours(time(13, 0)));
+        features.quiet_hours = Some((time(12, 0), time(14, 0)));
+        assert!(features.in_quiet_hours(time(12, 0)));
+        assert!(features.in_quiet_hours(time(13, 59)));
+        assert!(!features.in_quiet_hours(time(14, 0)));
+        assert!(!features.in_quiet_hours(time(11, 59)));
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let features = Features {
+            quiet_hours: Some((time(22, 0), time(7, 0))),
+            ..Features::default()
+        };
+        assert!(features.in_quiet_hours(time(22, 0)));
+        assert!(features.in_quiet_hours(time(23, 30)));
+        assert!(features.in_quiet_hours(time(0, 0)));
+        assert!(features.in_quiet_hours(time(6, 59)));
+        assert!(!features.in_quiet_hours(time(7, 0)));
+        assert!(!features.in_quiet_hours(time(12, 0)));
+    }
+
+    #[test]
+    fn nudging_a_channel_clamps_at_the_ends() {
+        let color = Color::Rgb(3, 128, 250);
+        assert_eq!(nudge_channel(color, 0, -5), Color::Rgb(0, 128, 250));
+        assert_eq!(nudge_channel(color, 1, 5), Color::Rgb(3, 133, 250));
+        assert_eq!(nudge_channel(color, 2, 5), Color::Rgb(3, 128, 255));
+        assert_eq!(nudge_channel(color, 3, 5), color);
+        assert_eq!(nudge_channel(Color::Red, 0, 5), Color::Red);
+    }
+
+    #[test]
+    fn hex_colors_round_trip() {
+        use serde::de::{value::Error, IntoDeserializer};
+        for color in [
+            Color::Rgb(0, 0, 0),
+            Color::Rgb(230, 195, 132),
+            Color::Rgb(255, 255, 255),
+        ] {
+            let hex = color_to_hex(color).unwrap();
+            let parsed = hex_to_color(hex.as_str().into_deserializer()).map_err(|e: Error| e);
+            assert_eq!(parsed.unwrap(), color, "{}", hex);
         }
-        None => Ok(Config::default()),
+        assert_eq!(color_to_hex(Color::Rgb(230, 195, 132)).unwrap(), "#e6c384");
+        assert_eq!(color_to_hex(Color::Red), None);
+    }
+
+    #[test]
+    fn border_types_parse_by_name() {
+        for (name, expected) in [
+            ("plain", BorderType::Plain),
+            ("rounded", BorderType::Rounded),
+            ("Double", BorderType::Double),
+            ("THICK", BorderType::Thick),
+        ] {
+            let toml = format!("panes = {:?}", name);
+            let borders: BorderStyleConfig = toml::from_str(&toml).unwrap();
+            assert_eq!(borders.panes, expected);
+            assert_eq!(borders.overlays, BorderType::Double);
+        }
+        let err = toml::from_str::<BorderStyleConfig>("overlays = \"dashed\"").unwrap_err();
+        assert!(err.to_string().contains("unknown border type \"dashed\""));
+    }
+
+    #[test]
+    fn data_dir_override_also_holds_the_config() {
+        let data_dir = PathBuf::from("/portable/kronos");
+        let platform = Some(PathBuf::from("/home/me/.config/kronos"));
+        assert_eq!(
+            config_path_for(Some(data_dir), platform.clone()),
+            Some("/portable/kronos/kronos.toml".into())
+        );
+        assert_eq!(
+            config_path_for(None, platform),
+            Some("/home/me/.config/kronos/kronos.toml".into())
+        );
+    }
+
+    #[test]
+    fn startup_view_rejects_unknown_names() {
+        let features: Features = toml::from_str("startup_view = \"stats\"").unwrap();
+        assert!(matches!(features.startup_view, StartupView::Stats));
+        assert!(toml::from_str::<Features>("startup_view = \"calendar\"").is_err());
+    }
+
+    #[test]
+    fn effect_durations_out_of_range_are_rejected() {
+        let effects: EffectDurations = toml::from_str("delete = 10\nstreak = 10000").unwrap();
+        assert_eq!((effects.delete, effects.streak), (10, 10_000));
+        let err = toml::from_str::<EffectDurations>("delete = 5").unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+        assert!(toml::from_str::<EffectDurations>("complete = 10001").is_err());
+    }
+
+    #[test]
+    fn explicit_effects_setting_beats_reduced_motion() {
+        assert!(effects_active_from(None, false));
+        assert!(!effects_active_from(None, true));
+        assert!(effects_active_from(Some(true), true));
+        assert!(!effects_active_from(Some(false), false));
     }
 }
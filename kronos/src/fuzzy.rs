@@ -0,0 +1,62 @@
+/// Returns the indices of `items` that contain `query` as a case-insensitive
+/// subsequence, best match first. Tighter matches rank higher, then ones
+/// starting earlier; ties keep their original order. An empty query
+/// matches everything.
+pub fn fuzzy_filter(items: &[String], query: &str) -> Vec<usize> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let mut scored: Vec<((usize, usize), usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| score(item, &query).map(|s| (s, i)))
+        .collect();
+    scored.sort();
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Scores a match as `(span, start)`, lower being better.
+fn score(item: &str, query: &[char]) -> Option<(usize, usize)> {
+    if query.is_empty() {
+        return Some((0, 0));
+    }
+    let mut matched = 0;
+    let mut first = None;
+    for (pos, ch) in item.to_lowercase().chars().enumerate() {
+        if ch == query[matched] {
+            let start = *first.get_or_insert(pos);
+            matched += 1;
+            if matched == query.len() {
+                return Some((pos - start, start));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presets() -> Vec<String> {
+        ["Pomodoro", "Short Break", "Long Break"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn tighter_matches_rank_first() {
+        // "r..k" spans 7 characters in "Short Break" but 3 in "Long Break".
+        assert_eq!(fuzzy_filter(&presets(), "RK"), [2, 1]);
+        assert_eq!(fuzzy_filter(&presets(), "pmd"), [0]);
+    }
+
+    #[test]
+    fn letters_must_appear_in_order() {
+        assert!(fuzzy_filter(&presets(), "kr").is_empty());
+    }
+
+    #[test]
+    fn empty_query_keeps_everything_in_order() {
+        assert_eq!(fuzzy_filter(&presets(), ""), [0, 1, 2]);
+    }
+}
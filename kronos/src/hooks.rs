@@ -0,0 +1,104 @@
+//! User commands run when tasks start or complete, configured as
+//! `on_start_command` and `on_complete_command`.
+//!
+//! Templates may use `{id}`, `{description}` and `{minutes}` (elapsed,
+//! whole minutes). Values are substituted already shell-quoted, so write
+//! `notify-send {description}` rather than wrapping placeholders in quotes.
+
+use crate::app::Task;
+use anyhow::{Context, Result};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+
+/// Fills the placeholders in `template` from `task`.
+pub fn hook_command_line(template: &str, task: &Task) -> String {
+    template
+        .replace("{id}", &task.id.to_string())
+        .replace("{description}", &shell_quote(&task.description))
+        .replace(
+            "{minutes}",
+            &task.timer.get_elapsed().num_minutes().max(0).to_string(),
+        )
+}
+
+/// Starts the hook through `sh -c` and returns without waiting for it. The
+/// child is reaped on a background thread, which logs a non-zero exit along
+/// with whatever the hook wrote to stderr.
+pub fn run_hook(template: &str, task: &Task) -> Result<()> {
+    let command_line = hook_command_line(template, task);
+    let child = spawn_hook(&command_line)?;
+    thread::spawn(move || {
+        if let Some(failure) = reap_hook(&command_line, child) {
+            tracing::warn!("{}", failure);
+        }
+    });
+    Ok(())
+}
+
+fn spawn_hook(command_line: &str) -> Result<Child> {
+    Command::new("sh")
+        .arg("-c")
+        .arg(command_line)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run hook `{}`", command_line))
+}
+
+/// Waits for a hook to exit. Returns a description of the failure if it
+/// couldn't be waited on or exited unsuccessfully.
+fn reap_hook(command_line: &str, child: Child) -> Option<String> {
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(e) => return Some(format!("Failed to wait for hook `{}`: {}", command_line, e)),
+    };
+    if output.status.success() {
+        return None;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut failure = format!("Hook `{}` failed with {}", command_line, output.status);
+    if !stderr.trim().is_empty() {
+        failure.push_str(&format!(": {}", stderr.trim()));
+    }
+    Some(failure)
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::test_app;
+
+    #[test]
+    fn command_line_fills_in_quoted_placeholders() {
+        let mut app = test_app();
+        app.add_task("Don't stop".to_string()).unwrap();
+        let task = &mut app.tasks[0];
+        task.timer.accumulated_time = chrono::Duration::seconds(25 * 60 + 59);
+        assert_eq!(
+            hook_command_line("log {id} {description} {minutes}m", task),
+            r"log 1 'Don'\''t stop' 25m"
+        );
+        assert_eq!(hook_command_line("true", task), "true");
+    }
+
+    #[test]
+    fn failing_hooks_are_reported_with_their_stderr() {
+        let ok = spawn_hook("true").unwrap();
+        assert_eq!(reap_hook("true", ok), None);
+
+        let command_line = "echo 'no such player' >&2; exit 3";
+        let failed = spawn_hook(command_line).unwrap();
+        assert_eq!(
+            reap_hook(command_line, failed).unwrap(),
+            format!(
+                "Hook `{}` failed with exit status: 3: no such player",
+                command_line
+            )
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! The key bindings, as listed in the help overlay and the exported
+//! cheatsheet.
+
+/// Every key binding, grouped by section.
+pub fn keybindings() -> &'static [(&'static str, &'static [(&'static str, &'static str)])] {
+    &[
+        (
+            "General",
+            &[
+                ("q", "Quit"),
+                ("s", "Show Stats"),
+                ("?", "Toggle help"),
+                ("v", "Toggle compact view"),
+                (":", "Toggle seconds display"),
+                ("m", "Toggle animations"),
+                ("C", "Edit theme colors"),
+                ("K", "Export this cheatsheet"),
+            ],
+        ),
+        (
+            "Tasks",
+            &[
+                ("a", "Add task"),
+                ("o/O", "Add task below/above"),
+                ("y", "Duplicate task"),
+                ("d", "Delete task"),
+                ("x", "Toggle complete"),
+                ("i", "Task details"),
+                ("R", "Rename task"),
+                ("w", "Set due date"),
+                ("Space", "Start/pause timer"),
+                ("S", "Stop timer, keeping elapsed"),
+                ("r", "Reset timer"),
+                ("l", "Mark a lap"),
+                ("z", "Snooze timer"),
+                ("t", "Set time"),
+                ("e", "Estimate Pomodoros"),
+                ("p", "Select preset"),
+                ("P", "Manage presets"),
+                ("c", "Change category"),
+                ("f", "Cycle category"),
+                ("b", "Split into subtasks"),
+                (">", "Defer to tomorrow"),
+                ("D", "Show/hide deferred tasks"),
+                ("A", "Show only active timers"),
+                ("M", "Merge duplicate tasks"),
+                ("u", "Undo last edit"),
+                ("C-r", "Redo"),
+            ],
+        ),
+        (
+            "Navigation",
+            &[
+                ("j/↓", "Move down"),
+                ("k/↑", "Move up"),
+                ("Tab", "Next incomplete task"),
+                ("S-Tab", "Previous incomplete task"),
+                ("n", "Select suggested next task"),
+            ],
+        ),
+        (
+            "Global Timer",
+            &[
+                ("g", "Start/pause global timer"),
+                ("G", "Reset global timer"),
+                ("T", "Set global timer preset"),
+            ],
+        ),
+    ]
+}
+
+/// Markdown cheatsheet of `keybindings()`, one line per binding.
+pub fn cheatsheet() -> String {
+    let mut out = String::from("# Kronos key bindings\n");
+    for (section, keys) in keybindings() {
+        out.push_str(&format!("\n## {}\n\n", section));
+        for (key, desc) in keys.iter() {
+            out.push_str(&format!("- `{}`: {}\n", key, desc));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cheatsheet_lists_every_binding_once() {
+        let sheet = cheatsheet();
+        for (section, keys) in keybindings() {
+            assert_eq!(sheet.matches(&format!("## {}\n", section)).count(), 1);
+            for (key, desc) in keys.iter() {
+                let line = format!("- `{}`: {}\n", key, desc);
+                assert_eq!(sheet.matches(&line).count(), 1, "{}", line);
+            }
+        }
+        let actions: Vec<&str> = keybindings()
+            .iter()
+            .flat_map(|(_, keys)| keys.iter().map(|(_, desc)| *desc))
+            .collect();
+        let unique: std::collections::HashSet<&str> = actions.iter().copied().collect();
+        assert_eq!(unique.len(), actions.len());
+        let lines = sheet.lines().filter(|l| l.starts_with("- ")).count();
+        assert_eq!(lines, actions.len());
+    }
+}
@@ -0,0 +1,71 @@
+//! Single-instance guard so two kronos processes don't clobber each
+//! other's persisted state.
+//!
+//! The guard is an advisory lock on a file in the data dir. The OS drops
+//! the lock when the holder exits, so a crashed instance can't leave a
+//! stale lock behind.
+
+use anyhow::{Context, Result};
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    io::Write,
+    path::Path,
+};
+
+pub struct InstanceLock {
+    file: File,
+}
+
+impl InstanceLock {
+    /// Takes the lock at `path`, or returns `None` if another process holds it.
+    pub fn acquire(path: &Path) -> Result<Option<Self>> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("Failed to open lock file at {:?}", path))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {:?}", path))
+            }
+        }
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        file.flush()?;
+        Ok(Some(Self { file }))
+    }
+
+    /// Pid recorded by whoever holds (or last held) the lock at `path`.
+    pub fn holder(path: &Path) -> Option<u32> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_is_exclusive_until_released() {
+        let path = std::env::temp_dir().join(format!("kronos-lock-{}", std::process::id()));
+        let lock = InstanceLock::acquire(&path).unwrap().expect("lock is free");
+        assert_eq!(InstanceLock::holder(&path), Some(std::process::id()));
+        assert!(InstanceLock::acquire(&path).unwrap().is_none());
+
+        drop(lock);
+        let relocked = InstanceLock::acquire(&path).unwrap();
+        assert!(relocked.is_some());
+        drop(relocked);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,12 +1,24 @@
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    cursor::Show,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    style::Print,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+    },
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::{
-    io::{self, Stdout},
+    fs,
+    io::{self, Stdout, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 // Import the correct Duration type from the tachyonfx crate.
@@ -14,33 +26,76 @@ use tachyonfx::Duration as TachyonDuration;
 
 mod app;
 mod config;
+mod fuzzy;
+mod hooks;
+mod keys;
+mod lock;
 mod persistence;
+mod server;
 mod ui;
 
 use app::{App, AppMode, TaskCategory};
+use kronos_ipc::TimerState;
+use lock::InstanceLock;
 use persistence::Persistence;
+use server::Requests;
 use ui::UiLayout;
 
+/// How far one arrow press moves a color channel in the theme editor.
+const THEME_NUDGE_STEP: i16 = 5;
+
+/// Timer events kept for a subscriber that's slow to read them.
+const EVENT_BUFFER: usize = 64;
+
 fn main() -> Result<()> {
+    if let Some(dir) = data_dir_arg(std::env::args().skip(1))? {
+        Persistence::set_data_dir_override(dir);
+    }
+    let lock_path = Persistence::data_dir()?.join("kronos.lock");
+    let Some(_lock) = InstanceLock::acquire(&lock_path)? else {
+        let pid = InstanceLock::holder(&lock_path)
+            .map(|pid| format!(" (pid {})", pid))
+            .unwrap_or_default();
+        eprintln!("kronos is already running{}.", pid);
+        eprintln!("Use kronosctl to control the running instance.");
+        std::process::exit(1);
+    };
+
+    let config = config::load_config()?;
+    let mut app = Persistence::load(&config)?.unwrap_or_else(|| App::new(config.clone()));
+    app.config = config;
+
+    let set_title = app.config.features.set_terminal_title;
+    install_panic_hook();
     enable_raw_mode()?;
+    let guard = TerminalGuard(CrosstermTerminal);
     let mut stdout = io::stdout();
+    if set_title {
+        // xterm: push the current title so it can be restored on exit
+        write!(stdout, "\x1b[22;0t")?;
+    }
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let config = config::load_config()?;
-    let mut app = Persistence::load(&config)?.unwrap_or_else(|| App::new(config.clone()));
-    app.config = config;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let (request_tx, mut requests) = tokio::sync::mpsc::unbounded_channel();
+    let (event_tx, _) = tokio::sync::broadcast::channel(EVENT_BUFFER);
+    app.events = Some(event_tx.clone());
+    runtime.spawn(server::run(request_tx, event_tx));
+    let reload = Arc::new(AtomicBool::new(false));
+    runtime.spawn(watch_sighup(reload.clone()));
+
+    let res = run_app(&mut terminal, &mut app, &mut requests, &reload);
 
-    let res = run_app(&mut terminal, &mut app);
+    drop(guard);
+    if set_title {
+        write!(terminal.backend_mut(), "\x1b[23;0t")?;
+        terminal.backend_mut().flush()?;
+    }
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    runtime.shutdown_background();
+    let _ = fs::remove_file(kronos_ipc::SOCKET_PATH);
 
     if let Err(err) = res {
         eprintln!("Error: {:?}", err);
@@ -49,38 +104,165 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) -> Result<()> {
+/// The steps that undo kronos's terminal setup, behind a trait so
+/// `TerminalGuard` can be tested without a real terminal.
+trait TerminalRestore {
+    fn disable_raw_mode(&mut self) -> io::Result<()>;
+    fn leave_alternate_screen(&mut self) -> io::Result<()>;
+    fn show_cursor(&mut self) -> io::Result<()>;
+}
+
+/// The terminal on stdout.
+struct CrosstermTerminal;
+
+impl TerminalRestore for CrosstermTerminal {
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        disable_raw_mode()
+    }
+    fn leave_alternate_screen(&mut self) -> io::Result<()> {
+        execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)
+    }
+    fn show_cursor(&mut self) -> io::Result<()> {
+        execute!(io::stdout(), Show)
+    }
+}
+
+/// Runs every restore step, carrying on past any that fail.
+fn restore_terminal(terminal: &mut impl TerminalRestore) {
+    let _ = terminal.disable_raw_mode();
+    let _ = terminal.leave_alternate_screen();
+    let _ = terminal.show_cursor();
+}
+
+/// Restores the terminal when dropped, whether `main` returns normally, bails
+/// out with an error or unwinds from a panic.
+struct TerminalGuard<T: TerminalRestore>(T);
+
+impl<T: TerminalRestore> Drop for TerminalGuard<T> {
+    fn drop(&mut self) {
+        restore_terminal(&mut self.0);
+    }
+}
+
+/// Restores the terminal before the default hook prints a panic, so the
+/// message isn't lost on the alternate screen. `TerminalGuard` only runs
+/// once unwinding starts, after the message is printed.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal(&mut CrosstermTerminal);
+        default_hook(info);
+    }));
+}
+
+/// Reads `--data-dir <path>` (or `--data-dir=<path>`) from the command line.
+fn data_dir_arg(mut args: impl Iterator<Item = String>) -> Result<Option<PathBuf>> {
+    let Some(arg) = args.next() else {
+        return Ok(None);
+    };
+    let dir = match arg.strip_prefix("--data-dir=") {
+        Some(dir) => dir.to_string(),
+        None if arg == "--data-dir" => args
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("--data-dir needs a path"))?,
+        None => anyhow::bail!("Unknown argument: {}", arg),
+    };
+    if let Some(extra) = args.next() {
+        anyhow::bail!("Unknown argument: {}", extra);
+    }
+    Ok(Some(PathBuf::from(dir)))
+}
+
+/// Raises `reload` on every SIGHUP, for `kill -HUP` to pick up config
+/// changes without a restart.
+async fn watch_sighup(reload: Arc<AtomicBool>) -> Result<()> {
+    let mut hangups = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+    while hangups.recv().await.is_some() {
+        reload.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Reloads the config if a SIGHUP came in since the last check. Tasks and
+/// timers are left as they are.
+fn apply_pending_reload(app: &mut App, reload: &AtomicBool) {
+    if reload.swap(false, Ordering::Relaxed) {
+        app.reload_config();
+    }
+}
+
+fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    app: &mut App,
+    requests: &mut Requests,
+    reload: &AtomicBool,
+) -> Result<()> {
     let mut last_save = Instant::now();
     let mut last_frame_time = Instant::now();
+    let mut last_title = String::new();
     let mut ui_layout = UiLayout::default();
 
     loop {
+        apply_pending_reload(app, reload);
+        server::process_pending(app, requests);
+        app.reconcile_selection();
+
         let now = Instant::now();
         let delta = now.duration_since(last_frame_time);
         last_frame_time = now;
+        app.rotate_tip_at(now);
+        app.advance_done_animations(now);
+        app.finish_startup_at(now);
+        if app.idle_quit_due(now) {
+            app.should_quit = true;
+        }
 
         terminal.draw(|f| {
             let frame_area = f.area();
             ui_layout = ui::draw(f, app);
             
-            // Correctly convert std::time::Duration to tachyonfx::Duration.
-            let tachyon_delta = TachyonDuration::from_millis(delta.as_millis() as u32);
-            app.effect_manager
-                .process_effects(tachyon_delta, f.buffer_mut(), frame_area);
+            if app.effects_active() {
+                // Correctly convert std::time::Duration to tachyonfx::Duration.
+                let tachyon_delta = TachyonDuration::from_millis(delta.as_millis() as u32);
+                app.effect_manager
+                    .process_effects(tachyon_delta, f.buffer_mut(), frame_area);
+            }
         })?;
 
-        app.check_and_notify_completions();
+        let running = app
+            .tasks
+            .iter()
+            .position(|t| t.timer.state == TimerState::Running);
+        if let Some(&rect) = running.and_then(|i| ui_layout.tasks.get(i)) {
+            app.trigger_running_pulse(rect);
+        }
 
-        if last_save.elapsed() > Duration::from_secs(app.config.features.auto_save_interval) {
-            if Persistence::save(app).is_ok() {
-                last_save = Instant::now();
+        if app.config.features.set_terminal_title {
+            let title = app.terminal_title();
+            if title != last_title {
+                execute!(terminal.backend_mut(), SetTitle(&title))?;
+                last_title = title;
             }
         }
 
+        app.check_and_notify_completions();
+        if app.take_bell() {
+            execute!(terminal.backend_mut(), Print('\x07'))?;
+        }
+
+        if last_save.elapsed() > Duration::from_secs(app.config.features.auto_save_interval)
+            && Persistence::save(app).is_ok()
+        {
+            last_save = Instant::now();
+        }
+
         if event::poll(Duration::from_millis(16))? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
                     let prev_mode = app.mode.clone();
+                    app.status_message = None;
+                    app.note_activity(Instant::now());
+                    let global_timer = app.config.features.show_global_timer;
 
                     match app.mode {
                         AppMode::Normal => match key.code {
@@ -106,61 +288,205 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                                 app.mode = AppMode::AddingTask;
                                 app.input_buffer.clear();
                             }
+                            KeyCode::Char('O') => {
+                                app.mode = AppMode::InsertingTask(app.selected_task);
+                                app.input_buffer.clear();
+                            }
+                            KeyCode::Char('o') => {
+                                let below = (app.selected_task + 1).min(app.tasks.len());
+                                app.mode = AppMode::InsertingTask(below);
+                                app.input_buffer.clear();
+                            }
+                            KeyCode::Char('y') => app.duplicate_selected_task(),
+                            KeyCode::Char('>') => app.defer_selected_task(1),
+                            KeyCode::Char('D') => app.toggle_show_deferred(),
+                            KeyCode::Char('A') => app.toggle_show_only_active(),
                             KeyCode::Char(' ') => app.toggle_selected_timer(),
+                            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                app.redo()
+                            }
                             KeyCode::Char('r') => app.reset_selected_timer(),
-                            KeyCode::Char('t') => {
-                                if !app.tasks.is_empty() {
-                                    app.mode = AppMode::EditingTime(app.selected_task);
-                                    app.input_buffer.clear();
+                            KeyCode::Char('u') => app.undo(),
+                            KeyCode::Char('S') => app.stop_selected_timer(),
+                            KeyCode::Char('l') => {
+                                if let Some(task) = app.tasks.get_mut(app.selected_task) {
+                                    task.timer.lap();
                                 }
                             }
-                            KeyCode::Char('p') => {
-                                if !app.tasks.is_empty() {
-                                    app.mode = AppMode::SelectingPreset(app.selected_task);
-                                }
+                            KeyCode::Char('z') => {
+                                app.snooze_selected_timer(app.config.features.snooze_minutes)
                             }
-                            KeyCode::Char('c') => {
-                                if !app.tasks.is_empty() {
-                                    app.mode = AppMode::SelectingCategory(app.selected_task);
-                                    app.category_list_state.select(Some(0));
-                                }
+                            KeyCode::Char('t') if !app.tasks.is_empty() => {
+                                app.mode = AppMode::EditingTime(app.selected_task);
+                                app.input_buffer.clear();
+                            }
+                            KeyCode::Char('e') if !app.tasks.is_empty() => {
+                                app.mode = AppMode::EditingEstimate(app.selected_task);
+                                app.input_buffer.clear();
+                            }
+                            KeyCode::Char('p') if !app.tasks.is_empty() => {
+                                app.mode = AppMode::SelectingPreset(app.selected_task);
+                                app.input_buffer.clear();
+                            }
+                            KeyCode::Char('c') if !app.tasks.is_empty() => {
+                                app.mode = AppMode::SelectingCategory(app.selected_task);
+                                app.category_list_state.select(Some(0));
+                                app.input_buffer.clear();
+                            }
+                            KeyCode::Char('f') => app.cycle_selected_task_category(),
+                            KeyCode::Char('b') => app.start_split_selected_task(),
+                            KeyCode::Char('R') => app.start_rename_selected_task(),
+                            KeyCode::Char('w') => app.start_edit_due_selected_task(),
+                            KeyCode::Char('n') => app.select_suggested_task(),
+                            KeyCode::Char('P') => {
+                                app.mode = AppMode::ManagingPresets;
+                                app.preset_list_state.select(Some(0));
+                            }
+                            KeyCode::Char('v') => app.compact_view = !app.compact_view,
+                            KeyCode::Char('i') if !app.tasks.is_empty() => {
+                                app.mode = AppMode::ShowDetail(app.selected_task);
                             }
                             KeyCode::Char('s') => app.mode = AppMode::ShowStats,
                             KeyCode::Char('?') => app.mode = AppMode::ShowHelp,
-                            KeyCode::Char('g') => app.global_timer.toggle(),
-                            KeyCode::Char('G') => {
+                            KeyCode::Char('C') => app.open_theme_editor(),
+                            KeyCode::Char('K') => app.export_cheatsheet(),
+                            KeyCode::Char('M') => app.request_merge_duplicates(),
+                            KeyCode::Char(':') => app.toggle_show_seconds(),
+                            KeyCode::Char('m') => app.toggle_effects(),
+                            KeyCode::Char('g') if global_timer => app.global_timer.toggle(),
+                            KeyCode::Char('T') if global_timer => {
+                                app.mode = AppMode::SelectingGlobalPreset;
+                                app.input_buffer.clear();
+                            }
+                            KeyCode::Char('G') if global_timer => {
                                 app.global_timer.reset();
                                 app.notifications_sent.retain(|&id| id != 0);
                             }
                             KeyCode::Up | KeyCode::Char('k') => app.move_selection_up(),
                             KeyCode::Down | KeyCode::Char('j') => app.move_selection_down(),
+                            KeyCode::Tab => app.select_next_incomplete(),
+                            KeyCode::BackTab => app.select_prev_incomplete(),
                             _ => {}
                         },
                         AppMode::SelectingCategory(task_idx) => match key.code {
-                            KeyCode::Up | KeyCode::Char('k') => {
-                                // let category_count = app.get_category_names().len();
+                            KeyCode::Up => {
                                 let selected = app.category_list_state.selected().unwrap_or(0);
-                                app.category_list_state.select(Some(selected.saturating_sub(1)));
+                                app.category_list_state
+                                    .select(Some(selected.saturating_sub(1)));
                             }
-                            KeyCode::Down | KeyCode::Char('j') => {
-                                let category_count = app.get_category_names().len();
+                            KeyCode::Down => {
+                                let category_count = app.filtered_category_names().len();
                                 let selected = app.category_list_state.selected().unwrap_or(0);
-                                app.category_list_state.select(Some((selected + 1).min(category_count - 1)));
+                                app.category_list_state.select(Some(
+                                    (selected + 1).min(category_count.saturating_sub(1)),
+                                ));
                             }
                             KeyCode::Enter => {
-                                if let Some(selected) = app.category_list_state.selected() {
-                                    let category = match selected {
-                                        0 => TaskCategory::Work,
-                                        1 => TaskCategory::Personal,
-                                        2 => TaskCategory::Study,
-                                        3 => TaskCategory::Exercise,
-                                        _ => TaskCategory::Other("General".to_string()),
-                                    };
-                                    app.set_task_category(task_idx, category);
+                                let names = app.filtered_category_names();
+                                let selected = app.category_list_state.selected().unwrap_or(0);
+                                if let Some(name) = names.get(selected) {
+                                    app.set_task_category(task_idx, TaskCategory::from_name(name));
+                                    app.input_buffer.clear();
+                                    app.mode = AppMode::Normal;
                                 }
-                                app.mode = AppMode::Normal;
+                            }
+                            KeyCode::Esc if !app.input_buffer.is_empty() => {
+                                app.input_buffer.clear();
+                                app.category_list_state.select(Some(0));
                             }
                             KeyCode::Esc => app.mode = AppMode::Normal,
+                            KeyCode::Backspace => app.handle_backspace(),
+                            KeyCode::Char(c) => app.handle_char(c),
+                            _ => {}
+                        },
+                        AppMode::SelectingPreset(_) | AppMode::SelectingGlobalPreset => {
+                            match key.code {
+                                KeyCode::Enter => app.handle_char('\n'),
+                                KeyCode::Esc if !app.input_buffer.is_empty() => {
+                                    app.input_buffer.clear()
+                                }
+                                KeyCode::Esc => app.mode = AppMode::Normal,
+                                KeyCode::Backspace => app.handle_backspace(),
+                                KeyCode::Char(c) => app.handle_char(c),
+                                _ => {}
+                            }
+                        }
+                        // Any key skips the rest of the animation.
+                        AppMode::StartupAnimation => app.mode = app.startup_mode(),
+                        AppMode::ConfirmingMerge => {
+                            if key.code == KeyCode::Char('y') {
+                                let merged = app.merge_duplicates();
+                                app.status_message =
+                                    Some(format!("Merged {} duplicate task(s)", merged));
+                            }
+                            app.mode = AppMode::Normal;
+                        }
+                        AppMode::EditingTheme => match key.code {
+                            KeyCode::Up | KeyCode::Char('k') => app.move_theme_field(-1),
+                            KeyCode::Down | KeyCode::Char('j') => app.move_theme_field(1),
+                            KeyCode::Char('r') => app.theme_editor.channel = 0,
+                            KeyCode::Char('g') => app.theme_editor.channel = 1,
+                            KeyCode::Char('b') => app.theme_editor.channel = 2,
+                            KeyCode::Left | KeyCode::Char('h') => {
+                                app.nudge_theme_color(-THEME_NUDGE_STEP)
+                            }
+                            KeyCode::Right | KeyCode::Char('l') => {
+                                app.nudge_theme_color(THEME_NUDGE_STEP)
+                            }
+                            KeyCode::Enter => app.save_theme(),
+                            KeyCode::Esc => app.cancel_theme_editor(),
+                            _ => {}
+                        },
+                        AppMode::ManagingPresets => match key.code {
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                let selected = app.preset_list_state.selected().unwrap_or(0);
+                                app.preset_list_state
+                                    .select(Some(selected.saturating_sub(1)));
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                let preset_count = app.presets.len();
+                                let selected = app.preset_list_state.selected().unwrap_or(0);
+                                app.preset_list_state.select(Some(
+                                    (selected + 1).min(preset_count.saturating_sub(1)),
+                                ));
+                            }
+                            KeyCode::Char('a') => {
+                                app.mode = AppMode::EditingPresetName(None);
+                                app.input_buffer.clear();
+                            }
+                            KeyCode::Char('r') => {
+                                if let Some(name) = app.selected_preset_name() {
+                                    app.input_buffer = name.clone();
+                                    app.mode = AppMode::EditingPresetName(Some(name));
+                                }
+                            }
+                            KeyCode::Char('t') => {
+                                if let Some(name) = app.selected_preset_name() {
+                                    app.input_buffer.clear();
+                                    app.mode = AppMode::EditingPresetMinutes(name);
+                                }
+                            }
+                            KeyCode::Char('d') => app.delete_selected_preset(),
+                            KeyCode::Esc | KeyCode::Char('q') => app.mode = AppMode::Normal,
+                            _ => {}
+                        },
+                        AppMode::EditingPresetName(_) | AppMode::EditingPresetMinutes(_) => {
+                            match key.code {
+                                KeyCode::Enter => app.handle_char('\n'),
+                                KeyCode::Esc => {
+                                    app.input_buffer.clear();
+                                    app.mode = AppMode::ManagingPresets;
+                                }
+                                KeyCode::Backspace => app.handle_backspace(),
+                                KeyCode::Char(c) => app.handle_char(c),
+                                _ => {}
+                            }
+                        }
+                        AppMode::SplittingTask(_) => match key.code {
+                            KeyCode::Enter => app.handle_char('\n'),
+                            KeyCode::Esc => app.cancel_split_task(),
+                            KeyCode::Backspace => app.handle_backspace(),
+                            KeyCode::Char(c) => app.handle_char(c),
                             _ => {}
                         },
                         _ => match key.code {
@@ -187,3 +513,99 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::test_app;
+
+    /// Records restore calls, failing the ones named in `failing`.
+    struct FakeTerminal<'a> {
+        calls: &'a mut Vec<&'static str>,
+        failing: &'static [&'static str],
+    }
+
+    impl FakeTerminal<'_> {
+        fn call(&mut self, name: &'static str) -> io::Result<()> {
+            self.calls.push(name);
+            if self.failing.contains(&name) {
+                return Err(io::Error::other(name));
+            }
+            Ok(())
+        }
+    }
+
+    impl TerminalRestore for FakeTerminal<'_> {
+        fn disable_raw_mode(&mut self) -> io::Result<()> {
+            self.call("raw mode")
+        }
+        fn leave_alternate_screen(&mut self) -> io::Result<()> {
+            self.call("alternate screen")
+        }
+        fn show_cursor(&mut self) -> io::Result<()> {
+            self.call("cursor")
+        }
+    }
+
+    const ALL_STEPS: [&str; 3] = ["raw mode", "alternate screen", "cursor"];
+
+    #[test]
+    fn dropping_the_guard_restores_the_terminal() {
+        let mut calls = vec![];
+        drop(TerminalGuard(FakeTerminal {
+            calls: &mut calls,
+            failing: &[],
+        }));
+        assert_eq!(calls, ALL_STEPS);
+
+        let mut calls = vec![];
+        drop(TerminalGuard(FakeTerminal {
+            calls: &mut calls,
+            failing: &["raw mode"],
+        }));
+        assert_eq!(calls, ALL_STEPS);
+    }
+
+    #[test]
+    fn the_guard_restores_the_terminal_on_panic() {
+        let mut calls = vec![];
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = TerminalGuard(FakeTerminal {
+                calls: &mut calls,
+                failing: &[],
+            });
+            panic!("render failed");
+        }));
+        assert!(result.is_err());
+        assert_eq!(calls, ALL_STEPS);
+    }
+
+    #[test]
+    fn pending_reload_reloads_the_config_and_keeps_timers() {
+        let mut app = test_app();
+        app.add_task("Write report".to_string()).unwrap();
+        app.tasks[0].timer.start();
+        let reload = AtomicBool::new(false);
+        apply_pending_reload(&mut app, &reload);
+        assert!(app.status_message.is_none());
+
+        reload.store(true, Ordering::Relaxed);
+        apply_pending_reload(&mut app, &reload);
+        assert!(!reload.load(Ordering::Relaxed));
+        let message = app.status_message.take().unwrap();
+        assert!(message.contains("reload"), "{}", message);
+        assert_eq!(app.tasks.len(), 1);
+        assert_eq!(app.tasks[0].timer.state, TimerState::Running);
+    }
+
+    #[test]
+    fn data_dir_arg_reads_both_flag_forms() {
+        let parse = |args: &[&str]| data_dir_arg(args.iter().map(|a| a.to_string()));
+        assert_eq!(parse(&[]).unwrap(), None);
+        assert_eq!(parse(&["--data-dir", "/d"]).unwrap(), Some("/d".into()));
+        assert_eq!(parse(&["--data-dir=/d"]).unwrap(), Some("/d".into()));
+        assert!(parse(&["--data-dir"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+        assert!(parse(&["--data-dir=/d", "extra"]).is_err());
+    }
+}
@@ -1,19 +1,54 @@
 use crate::app::default_effect_manager;
-use crate::app::App;
+use crate::app::{App, Task};
 use crate::config::Config;
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use std::{fs, path::PathBuf};
+use std::{env, ffi::OsString, fs, path::PathBuf, sync::OnceLock};
+
+/// Set once from `--data-dir` at startup.
+static DATA_DIR_FLAG: OnceLock<PathBuf> = OnceLock::new();
 
 pub struct Persistence;
 
 impl Persistence {
+    /// Uses `dir` for state and config instead of the platform dirs, taking
+    /// precedence over `KRONOS_DATA_DIR`. Only the first call has an effect.
+    pub fn set_data_dir_override(dir: PathBuf) {
+        let _ = DATA_DIR_FLAG.set(dir);
+    }
+
+    /// The directory from `--data-dir`, else from `KRONOS_DATA_DIR`.
+    pub fn data_dir_override() -> Option<PathBuf> {
+        resolve_override(DATA_DIR_FLAG.get().cloned(), env::var_os("KRONOS_DATA_DIR"))
+    }
+
+    /// Where state, locks and worklogs live: the override from
+    /// `data_dir_override`, else the platform data dir, else
+    /// `$XDG_DATA_HOME/kronos`, else `~/.kronos`.
+    pub fn data_dir() -> Result<PathBuf> {
+        if let Some(dir) = Self::data_dir_override() {
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create data directory {:?}", dir))?;
+            return Ok(dir);
+        }
+        let project_dir = ProjectDirs::from("com", "pabloagn", "Kronos")
+            .map(|dirs| dirs.data_dir().to_path_buf());
+        let Some(data_dir) = resolve_data_dir(
+            project_dir,
+            env::var_os("XDG_DATA_HOME"),
+            env::var_os("HOME"),
+        ) else {
+            // kronos can't take its instance lock without one, so this stops startup.
+            anyhow::bail!(
+                "No data directory found; set HOME, XDG_DATA_HOME or KRONOS_DATA_DIR, or pass --data-dir."
+            );
+        };
+        fs::create_dir_all(&data_dir)?;
+        Ok(data_dir)
+    }
+
     fn get_data_path() -> Result<PathBuf> {
-        let proj_dirs = ProjectDirs::from("com", "pabloagn", "Kronos")
-            .ok_or_else(|| anyhow::anyhow!("Could not find a valid home directory."))?;
-        let data_dir = proj_dirs.data_dir();
-        fs::create_dir_all(data_dir)?;
-        Ok(data_dir.join("state.json"))
+        Ok(Self::data_dir()?.join("state.json"))
     }
 
     pub fn save(app: &App) -> Result<()> {
@@ -34,10 +69,152 @@ impl Persistence {
         if json.is_empty() {
             return Ok(None);
         }
-        let mut app: App = serde_json::from_str(&json)
-            .with_context(|| format!("Failed to deserialize state from {:?}", path))?;
+        let mut app: App = match serde_json::from_str(&json) {
+            Ok(app) => app,
+            Err(e) => {
+                // Keep the damaged file around; the next save overwrites state.json.
+                let backup = path.with_extension("json.corrupt");
+                fs::copy(&path, &backup)
+                    .with_context(|| format!("Failed to back up corrupt state to {:?}", backup))?;
+                let tasks = salvage_tasks(&json);
+                eprintln!(
+                    "State at {:?} is corrupt ({}); recovered {} task(s), original saved to {:?}",
+                    path,
+                    e,
+                    tasks.len(),
+                    backup
+                );
+                let mut app = App::new(config.clone());
+                app.next_task_id = tasks.iter().map(|t| t.id + 1).max().unwrap_or(1);
+                app.tasks = tasks;
+                app
+            }
+        };
         app.config = config.clone();
         app.effect_manager = default_effect_manager(); // Re-initialize non-deserialized fields
+        app.mode = app.startup_mode();
+        app.restore_selection_by_id();
         Ok(Some(app))
     }
 }
+
+/// The `--data-dir` flag if given, else a non-empty `KRONOS_DATA_DIR`.
+fn resolve_override(flag: Option<PathBuf>, env: Option<OsString>) -> Option<PathBuf> {
+    flag.or_else(|| env.filter(|v| !v.is_empty()).map(PathBuf::from))
+}
+
+/// Picks the first usable data directory from the fallback chain.
+fn resolve_data_dir(
+    project_dir: Option<PathBuf>,
+    xdg_data_home: Option<OsString>,
+    home: Option<OsString>,
+) -> Option<PathBuf> {
+    let non_empty = |v: Option<OsString>| v.filter(|v| !v.is_empty()).map(PathBuf::from);
+    project_dir
+        .or_else(|| non_empty(xdg_data_home).map(|dir| dir.join("kronos")))
+        .or_else(|| non_empty(home).map(|dir| dir.join(".kronos")))
+}
+
+/// Pulls whatever complete, well-formed tasks can be found in the `tasks`
+/// array of a state file that failed to parse as a whole. Reading stops at
+/// the first element that isn't complete JSON, so a truncated file still
+/// yields the tasks written before the cut.
+fn salvage_tasks(json: &str) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let Some(key) = json.find("\"tasks\"") else {
+        return tasks;
+    };
+    let rest = json[key + "\"tasks\"".len()..].trim_start();
+    let Some(rest) = rest.strip_prefix(':') else {
+        return tasks;
+    };
+    let Some(mut rest) = rest.trim_start().strip_prefix('[') else {
+        return tasks;
+    };
+
+    loop {
+        let mut values = serde_json::Deserializer::from_str(rest).into_iter::<serde_json::Value>();
+        let Some(Ok(value)) = values.next() else {
+            break;
+        };
+        rest = &rest[values.byte_offset()..];
+        // Elements that are valid JSON but not a valid task are skipped.
+        if let Ok(task) = serde_json::from_value::<Task>(value) {
+            tasks.push(task);
+        }
+        match rest.trim_start().strip_prefix(',') {
+            Some(next) => rest = next,
+            None => break,
+        }
+    }
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::test_app;
+
+    #[test]
+    fn tasks_survive_corrupt_stats() {
+        let mut app = test_app();
+        app.add_task("Write report".to_string()).unwrap();
+        app.add_task("Send report".to_string()).unwrap();
+        let tasks = serde_json::to_string(&app.tasks).unwrap();
+        let json = format!(
+            r#"{{"tasks": {}, "next_task_id": 3, "stats": {{"total_completed": "many"#,
+            tasks
+        );
+        assert!(serde_json::from_str::<App>(&json).is_err());
+
+        let salvaged = salvage_tasks(&json);
+        let ids: Vec<u32> = salvaged.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [1, 2]);
+        assert_eq!(salvaged[1].description, "Send report");
+    }
+
+    #[test]
+    fn salvage_stops_at_a_truncated_task() {
+        let mut app = test_app();
+        app.add_task("Write report".to_string()).unwrap();
+        let task = serde_json::to_string(&app.tasks[0]).unwrap();
+        let json = format!(r#"{{"tasks": [{}, {}"#, task, &task[..task.len() / 2]);
+        assert_eq!(salvage_tasks(&json).len(), 1);
+    }
+
+    #[test]
+    fn data_dir_falls_back_through_platform_xdg_and_home() {
+        let platform = Some(PathBuf::from("/platform/kronos"));
+        let xdg = || Some(OsString::from("/xdg"));
+        let home = || Some(OsString::from("/home/me"));
+        assert_eq!(
+            resolve_data_dir(platform, xdg(), home()),
+            Some("/platform/kronos".into())
+        );
+        assert_eq!(
+            resolve_data_dir(None, xdg(), home()),
+            Some("/xdg/kronos".into())
+        );
+        assert_eq!(
+            resolve_data_dir(None, Some("".into()), home()),
+            Some("/home/me/.kronos".into())
+        );
+        assert_eq!(
+            resolve_data_dir(None, None, home()),
+            Some("/home/me/.kronos".into())
+        );
+        assert_eq!(resolve_data_dir(None, None, Some("".into())), None);
+        assert_eq!(resolve_data_dir(None, None, None), None);
+    }
+
+    #[test]
+    fn data_dir_flag_beats_the_env_override() {
+        let flag = || Some(PathBuf::from("/flag"));
+        let env = || Some(OsString::from("/env"));
+        assert_eq!(resolve_override(flag(), env()), flag());
+        assert_eq!(resolve_override(None, env()), Some("/env".into()));
+        assert_eq!(resolve_override(flag(), None), flag());
+        assert_eq!(resolve_override(None, Some("".into())), None);
+        assert_eq!(resolve_override(None, None), None);
+    }
+}
@@ -0,0 +1,849 @@
+//! IPC server that lets kronosctl drive the running kronos instance.
+//!
+//! `App` owns the (non-`Send`) effect manager, so it never leaves the UI
+//! thread. Client connections are accepted on the tokio runtime and each
+//! command is forwarded over a channel, together with a oneshot for the
+//! reply, to be applied by the render loop. Commands from concurrent
+//! clients are thereby applied one at a time, in arrival order, with no lock
+//! held across a client's I/O.
+//!
+//! `Command::Subscribe` is the exception: it never reaches the loop. The
+//! connection is kept open and fed from the broadcast channel `App::publish`
+//! sends timer events to.
+
+use crate::app::{cap_timer_minutes, App, Priority, Task, TaskCategory};
+use crate::keys;
+use anyhow::Result;
+use chrono::Local;
+use kronos_ipc::{
+    Command, CommandLogEntry, ImportFormat, Response, TimerEvent, TimerState, TimerStatus,
+    PROTOCOL_VERSION, SOCKET_PATH,
+};
+use serde::de::DeserializeOwned;
+use std::fs;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    net::{
+        unix::{OwnedReadHalf, OwnedWriteHalf},
+        UnixListener, UnixStream,
+    },
+    sync::{broadcast, mpsc, oneshot},
+};
+
+pub type Request = (Command, oneshot::Sender<Response>);
+pub type Requests = mpsc::UnboundedReceiver<Request>;
+
+/// How many processed commands `Command::History` can report.
+const HISTORY_LIMIT: usize = 100;
+
+/// Largest message a client may send, leaving room for big imports.
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// What `read_message` got from the client.
+enum Incoming<T> {
+    Message(T),
+    Malformed(String),
+    Closed,
+}
+
+pub async fn run(
+    requests: mpsc::UnboundedSender<Request>,
+    events: broadcast::Sender<TimerEvent>,
+) -> Result<()> {
+    // A socket left behind by a previous run would make bind fail.
+    let _ = fs::remove_file(SOCKET_PATH);
+    let listener = UnixListener::bind(SOCKET_PATH)?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let requests = requests.clone();
+        let events = events.subscribe();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(stream, requests, events).await {
+                tracing::warn!("IPC client error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_client(
+    stream: UnixStream,
+    requests: mpsc::UnboundedSender<Request>,
+    mut events: broadcast::Receiver<TimerEvent>,
+) -> Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut buf = Vec::new();
+
+    let reply = match read_message(&mut reader, &mut buf).await? {
+        Incoming::Message(Command::Hello { version }) => handshake(&version),
+        Incoming::Message(_) => Response::Error("Expected a Hello handshake first".to_string()),
+        Incoming::Malformed(e) => Response::Error(format!("Malformed message: {}", e)),
+        Incoming::Closed => return Ok(()),
+    };
+    if !matches!(reply, Response::Hello { .. }) {
+        return reply_and_close(reader, writer, &reply).await;
+    }
+    writer.write_all(&serde_json::to_vec(&reply)?).await?;
+    writer.write_all(b"\n").await?;
+
+    let command = match read_message(&mut reader, &mut buf).await? {
+        Incoming::Message(command) => command,
+        Incoming::Malformed(e) => {
+            let reply = Response::Error(format!("Malformed message: {}", e));
+            return reply_and_close(reader, writer, &reply).await;
+        }
+        Incoming::Closed => return Ok(()),
+    };
+    if matches!(command, Command::Subscribe) {
+        writer
+            .write_all(&serde_json::to_vec(&Response::Ok)?)
+            .await?;
+        writer.write_all(b"\n").await?;
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                // A slow reader misses events rather than stalling the UI.
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            };
+            // Fails once the subscriber hangs up, ending the loop.
+            writer
+                .write_all(&serde_json::to_vec(&Response::Event(event))?)
+                .await?;
+            writer.write_all(b"\n").await?;
+        }
+    }
+    let (reply_tx, reply_rx) = oneshot::channel();
+    requests.send((command, reply_tx))?;
+    let response = reply_rx.await?;
+    reply_and_close(reader, writer, &response).await
+}
+
+/// Sends the last reply, then reads whatever the client still sends (such as
+/// the newline after a command parsed without it) until it hangs up.
+/// Closing with unread input would reset the connection before the client
+/// got the reply.
+async fn reply_and_close(
+    mut reader: OwnedReadHalf,
+    mut writer: OwnedWriteHalf,
+    reply: &Response,
+) -> Result<()> {
+    writer.write_all(&serde_json::to_vec(reply)?).await?;
+    writer.write_all(b"\n").await?;
+    writer.shutdown().await?;
+    tokio::io::copy(&mut reader, &mut tokio::io::sink()).await?;
+    Ok(())
+}
+
+/// Reads until `buf` holds one complete JSON value, however the client split
+/// its writes and whether or not it ends in a newline. Bytes past the value
+/// stay in `buf` for the next call.
+async fn read_message<T: DeserializeOwned>(
+    reader: &mut (impl AsyncRead + Unpin),
+    buf: &mut Vec<u8>,
+) -> Result<Incoming<T>> {
+    let mut chunk = [0; 4096];
+    loop {
+        let mut values = serde_json::Deserializer::from_slice(buf).into_iter::<T>();
+        match values.next() {
+            Some(Ok(value)) => {
+                let used = values.byte_offset();
+                buf.drain(..used);
+                return Ok(Incoming::Message(value));
+            }
+            Some(Err(e)) if !e.is_eof() => return Ok(Incoming::Malformed(e.to_string())),
+            // Nothing yet, or a value cut off mid-way: wait for more.
+            _ => {}
+        }
+        if buf.len() > MAX_MESSAGE_BYTES {
+            return Ok(Incoming::Malformed("message too large".to_string()));
+        }
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(if buf.iter().all(u8::is_ascii_whitespace) {
+                Incoming::Closed
+            } else {
+                Incoming::Malformed("connection closed mid-message".to_string())
+            });
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+}
+
+/// Applies every command received since the last frame.
+pub fn process_pending(app: &mut App, requests: &mut Requests) {
+    if !requests.is_empty() {
+        app.anchor_selection();
+    }
+    while let Ok((command, reply)) = requests.try_recv() {
+        app.note_activity(std::time::Instant::now());
+        let response = process_command(app, command);
+        app.enforce_invariants();
+        // The client may have hung up; nothing to do about it here.
+        let _ = reply.send(response);
+    }
+}
+
+pub fn process_command(app: &mut App, command: Command) -> Response {
+    if !matches!(
+        command,
+        Command::Hello { .. }
+            | Command::History
+            | Command::Ping
+            | Command::Version
+            | Command::ListPresets
+            | Command::SuggestTask
+            | Command::GetTask { .. }
+    ) {
+        record_command(app, &command);
+    }
+    match command {
+        Command::Hello { version } => handshake(&version),
+        Command::Start => {
+            app.global_timer.start();
+            app.publish(TimerEvent::Started { id: 0 });
+            Response::Ok
+        }
+        Command::Pause => {
+            if app.global_timer.state != TimerState::Running {
+                return Response::Error(format!(
+                    "Can't pause the global timer: it's {:?}",
+                    app.global_timer.state
+                ));
+            }
+            app.global_timer.pause();
+            app.publish(TimerEvent::Paused { id: 0 });
+            Response::Ok
+        }
+        Command::Resume => {
+            if app.global_timer.state != TimerState::Paused {
+                return Response::Error(format!(
+                    "Can't resume the global timer: it's {:?}",
+                    app.global_timer.state
+                ));
+            }
+            app.global_timer.resume();
+            app.publish(TimerEvent::Started { id: 0 });
+            Response::Ok
+        }
+        Command::Stop => {
+            app.global_timer.stop();
+            app.publish(TimerEvent::Stopped { id: 0 });
+            Response::Ok
+        }
+        Command::Reset => {
+            app.global_timer.reset();
+            app.notifications_sent.retain(|&id| id != 0);
+            Response::Ok
+        }
+        Command::Status => Response::Status(get_status(app)),
+        Command::AddTask {
+            description,
+            category,
+            minutes,
+            priority,
+        } => {
+            let priority = match priority.as_deref().map(str::parse::<Priority>) {
+                None => None,
+                Some(Ok(priority)) => Some(priority),
+                Some(Err(e)) => return Response::Error(e),
+            };
+            let features = &app.config.features;
+            let minutes = match minutes {
+                Some(minutes) if minutes < 1 => {
+                    return Response::Error(format!("{} minutes is not a valid duration", minutes))
+                }
+                Some(minutes) => match cap_timer_minutes(
+                    minutes,
+                    features.max_timer_minutes,
+                    features.clamp_timer_minutes,
+                ) {
+                    Ok(minutes) => Some(minutes),
+                    Err(e) => return Response::Error(e.to_string()),
+                },
+                None => None,
+            };
+            let category = category.as_deref().map(TaskCategory::from_name);
+            match app.add_task_with(description, category, minutes, priority) {
+                Ok(()) => Response::Ok,
+                Err(e) => Response::Error(e.to_string()),
+            }
+        }
+        Command::ListTasks => Response::Tasks(get_tasks(app)),
+        Command::FilterTasks {
+            incomplete_pomodoros,
+        } => Response::Tasks(
+            get_tasks(app)
+                .into_iter()
+                .filter(|t| !incomplete_pomodoros || t.completed_pomodoros < t.estimated_pomodoros)
+                .collect(),
+        ),
+        Command::ImportTasks { format, data } => {
+            let summary = match format {
+                ImportFormat::Json => match app.import_from_json(&data) {
+                    Ok(summary) => summary,
+                    Err(e) => return Response::Error(format!("{:#}", e)),
+                },
+                ImportFormat::Csv => app.import_from_csv(&data),
+            };
+            Response::Imported {
+                imported: summary.imported,
+                skipped: summary.skipped,
+            }
+        }
+        Command::History => Response::History(app.command_log.iter().cloned().collect()),
+        Command::StartAll { category } => {
+            let category = category.as_deref().map(TaskCategory::from_name);
+            Response::Started {
+                count: app.start_all_pending(category.as_ref()),
+            }
+        }
+        Command::SelectTask { id } => {
+            if app.select_task_by_id(id) {
+                // Re-anchor so reconcile_selection keeps the new choice.
+                app.anchor_selection();
+                Response::Ok
+            } else {
+                Response::Error(format!("No task with id {}", id))
+            }
+        }
+        // Handled in handle_client, which keeps the connection open.
+        Command::Subscribe => Response::Error("Subscribe needs its own connection".to_string()),
+        Command::CategoryBreakdown => Response::CategoryBreakdown(app.category_breakdown()),
+        Command::Keybindings => Response::Cheatsheet(keys::cheatsheet()),
+        Command::Ping => Response::Pong {
+            version: PROTOCOL_VERSION.to_string(),
+        },
+        Command::ListPresets => Response::Presets(
+            app.get_preset_names()
+                .into_iter()
+                .map(|name| {
+                    let minutes = app.presets[&name];
+                    (name, minutes)
+                })
+                .collect(),
+        ),
+        Command::ApplyPreset { id, preset } => {
+            let Some(idx) = app.tasks.iter().position(|t| t.id == id) else {
+                return Response::Error(format!("No task with id {}", id));
+            };
+            if !app.presets.contains_key(&preset) {
+                return Response::Error(format!("No preset named {:?}", preset));
+            }
+            app.set_task_duration_from_preset(idx, &preset);
+            Response::Ok
+        }
+        Command::ToggleSubtask {
+            task_id,
+            subtask_index,
+        } => {
+            let Some(idx) = app.tasks.iter().position(|t| t.id == task_id) else {
+                return Response::Error(format!("No task with id {}", task_id));
+            };
+            match app.toggle_subtask(idx, subtask_index) {
+                Some(_) => Response::Ok,
+                None => Response::Error(format!(
+                    "Task {} has no subtask {} (it has {})",
+                    task_id,
+                    subtask_index,
+                    app.tasks[idx].subtasks.len()
+                )),
+            }
+        }
+        Command::GetTask { id } => match app.tasks.iter().find(|t| t.id == id) {
+            Some(task) => Response::Task(to_ipc_task(task, app.pomodoro_minutes())),
+            None => Response::Error(format!("No task with id {}", id)),
+        },
+        Command::SuggestTask => Response::Suggestion(
+            app.suggest_next_task()
+                .map(|idx| to_ipc_task(&app.tasks[idx], app.pomodoro_minutes())),
+        ),
+        Command::Version => Response::Version {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            protocol: PROTOCOL_VERSION.to_string(),
+        },
+    }
+}
+
+fn handshake(client_version: &str) -> Response {
+    if kronos_ipc::is_compatible(client_version) {
+        Response::Hello {
+            version: PROTOCOL_VERSION.to_string(),
+        }
+    } else {
+        Response::Error(format!(
+            "Incompatible protocol: client speaks {}, kronos speaks {}",
+            client_version, PROTOCOL_VERSION
+        ))
+    }
+}
+
+fn record_command(app: &mut App, command: &Command) {
+    if app.command_log.len() == HISTORY_LIMIT {
+        app.command_log.pop_front();
+    }
+    app.command_log.push_back(CommandLogEntry {
+        timestamp: Local::now().timestamp().max(0) as u64,
+        command: command.clone(),
+    });
+}
+
+fn get_status(app: &App) -> TimerStatus {
+    let timer = &app.global_timer;
+    TimerStatus {
+        state: timer.state.clone(),
+        elapsed: timer.get_elapsed().num_seconds().max(0) as u64,
+        total: timer.target_duration.num_seconds().max(0) as u64,
+    }
+}
+
+pub fn get_tasks(app: &App) -> Vec<kronos_ipc::Task> {
+    let pomodoro_minutes = app.pomodoro_minutes();
+    app.tasks
+        .iter()
+        .map(|task| to_ipc_task(task, pomodoro_minutes))
+        .collect()
+}
+
+pub fn to_ipc_task(task: &Task, pomodoro_minutes: i64) -> kronos_ipc::Task {
+    kronos_ipc::Task {
+        id: task.id,
+        description: task.description.clone(),
+        completed: task.completed,
+        category: task.category.to_string(),
+        priority: task.priority.to_string(),
+        elapsed_seconds: task.timer.get_elapsed().num_seconds().max(0) as u64,
+        estimated_pomodoros: task.estimated_pomodoros,
+        completed_pomodoros: task.completed_pomodoros(pomodoro_minutes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::test_app;
+
+    #[test]
+    fn ipc_task_carries_every_field() {
+        let mut app = test_app();
+        app.add_task_with(
+            "Write report".to_string(),
+            Some(TaskCategory::Study),
+            Some(50),
+            Some(Priority::High),
+        )
+        .unwrap();
+        let task = &mut app.tasks[0];
+        task.completed = true;
+        task.estimated_pomodoros = 3;
+        task.timer.accumulated_time = chrono::Duration::minutes(55);
+
+        let ipc = to_ipc_task(&app.tasks[0], 25);
+        assert_eq!(ipc.id, 1);
+        assert_eq!(ipc.description, "Write report");
+        assert!(ipc.completed);
+        assert_eq!(ipc.category, "Study");
+        assert_eq!(ipc.priority, "High");
+        assert_eq!(ipc.elapsed_seconds, 55 * 60);
+        assert_eq!(ipc.estimated_pomodoros, 3);
+        assert_eq!(ipc.completed_pomodoros, 2);
+    }
+
+    #[test]
+    fn history_lists_commands_in_order() {
+        let mut app = test_app();
+        process_command(&mut app, Command::Start);
+        process_command(
+            &mut app,
+            Command::AddTask {
+                description: "Write report".to_string(),
+                category: None,
+                minutes: None,
+                priority: None,
+            },
+        );
+        process_command(&mut app, Command::Pause);
+        process_command(&mut app, Command::Ping);
+
+        let Response::History(entries) = process_command(&mut app, Command::History) else {
+            panic!("expected a history");
+        };
+        let commands: Vec<String> = entries
+            .iter()
+            .map(|entry| format!("{:?}", entry.command))
+            .collect();
+        assert_eq!(commands.len(), 3);
+        assert_eq!(commands[0], "Start");
+        assert!(commands[1].starts_with("AddTask"));
+        assert_eq!(commands[2], "Pause");
+        assert!(entries.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+    }
+
+    #[test]
+    fn history_keeps_only_the_latest_commands() {
+        let mut app = test_app();
+        for _ in 0..HISTORY_LIMIT + 5 {
+            process_command(&mut app, Command::Status);
+        }
+        assert_eq!(app.command_log.len(), HISTORY_LIMIT);
+    }
+
+    #[tokio::test]
+    async fn ping_round_trips_over_the_wire() {
+        let mut app = test_app();
+        let wire = serde_json::to_vec(&Command::Ping).unwrap();
+        let mut reader = wire.as_slice();
+        let mut buf = Vec::new();
+        let Incoming::Message(command) = read_message(&mut reader, &mut buf).await.unwrap() else {
+            panic!("expected a command");
+        };
+        let reply = serde_json::to_vec(&process_command(&mut app, command)).unwrap();
+        match serde_json::from_slice(&reply).unwrap() {
+            Response::Pong { version } => assert_eq!(version, PROTOCOL_VERSION),
+            other => panic!("expected a pong, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn handshake_rejects_another_major_version() {
+        assert!(matches!(handshake("1.9"), Response::Hello { .. }));
+        match handshake("2.0") {
+            Response::Error(e) => assert!(e.contains("client speaks 2.0")),
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+
+    fn add_task(description: &str) -> Command {
+        Command::AddTask {
+            description: description.to_string(),
+            category: None,
+            minutes: None,
+            priority: None,
+        }
+    }
+
+    #[test]
+    fn add_task_errors_at_the_task_limit() {
+        let mut app = test_app();
+        app.config.features.max_tasks = Some(1);
+        assert!(matches!(
+            process_command(&mut app, add_task("a")),
+            Response::Ok
+        ));
+        match process_command(&mut app, add_task("b")) {
+            Response::Error(e) => assert_eq!(e, "Task limit of 1 reached"),
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert_eq!(app.tasks.len(), 1);
+    }
+
+    #[test]
+    fn select_task_moves_the_selection() {
+        let mut app = test_app();
+        for description in ["a", "b", "c"] {
+            process_command(&mut app, add_task(description));
+        }
+        let id = app.tasks[2].id;
+        assert!(matches!(
+            process_command(&mut app, Command::SelectTask { id }),
+            Response::Ok
+        ));
+        assert_eq!(app.selected_task, 2);
+
+        match process_command(&mut app, Command::SelectTask { id: 99 }) {
+            Response::Error(e) => assert_eq!(e, "No task with id 99"),
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert_eq!(app.selected_task, 2);
+    }
+
+    #[test]
+    fn stop_keeps_the_global_elapsed_and_reset_zeroes_it() {
+        let mut app = test_app();
+        app.global_timer.accumulated_time = chrono::Duration::minutes(7);
+        process_command(&mut app, Command::Start);
+        process_command(&mut app, Command::Stop);
+        assert_eq!(app.global_timer.state, TimerState::Idle);
+        assert!(app.global_timer.accumulated_time >= chrono::Duration::minutes(7));
+
+        process_command(&mut app, Command::Reset);
+        assert_eq!(app.global_timer.state, TimerState::Idle);
+        assert_eq!(app.global_timer.accumulated_time, chrono::Duration::zero());
+    }
+
+    #[tokio::test]
+    async fn concurrent_add_tasks_get_distinct_ids() {
+        let mut app = test_app();
+        let (request_tx, mut requests) = mpsc::unbounded_channel();
+        let clients: Vec<_> = ["Write report", "Read mail"]
+            .into_iter()
+            .map(|description| {
+                let request_tx = request_tx.clone();
+                tokio::spawn(async move {
+                    let (reply_tx, reply_rx) = oneshot::channel();
+                    request_tx.send((add_task(description), reply_tx)).unwrap();
+                    reply_rx.await.unwrap()
+                })
+            })
+            .collect();
+        while requests.len() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        process_pending(&mut app, &mut requests);
+        for client in clients {
+            assert!(matches!(client.await.unwrap(), Response::Ok));
+        }
+        let mut ids: Vec<u32> = app.tasks.iter().map(|t| t.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, [1, 2]);
+        assert_eq!(app.next_task_id, 3);
+        assert!(app.selected_task < app.tasks.len());
+    }
+
+    #[test]
+    fn pause_and_resume_check_the_global_timer_state() {
+        let mut app = test_app();
+        match process_command(&mut app, Command::Resume) {
+            Response::Error(e) => assert_eq!(e, "Can't resume the global timer: it's Idle"),
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert!(matches!(
+            process_command(&mut app, Command::Pause),
+            Response::Error(_)
+        ));
+        assert_eq!(app.global_timer.state, TimerState::Idle);
+
+        process_command(&mut app, Command::Start);
+        assert!(matches!(
+            process_command(&mut app, Command::Pause),
+            Response::Ok
+        ));
+        assert_eq!(app.global_timer.state, TimerState::Paused);
+        assert!(matches!(
+            process_command(&mut app, Command::Resume),
+            Response::Ok
+        ));
+        assert_eq!(app.global_timer.state, TimerState::Running);
+    }
+
+    #[test]
+    fn add_task_with_every_field() {
+        let mut app = test_app();
+        let command = Command::AddTask {
+            description: "Write report".to_string(),
+            category: Some("study".to_string()),
+            minutes: Some(50),
+            priority: Some("High".to_string()),
+        };
+        assert!(matches!(process_command(&mut app, command), Response::Ok));
+        let task = &app.tasks[0];
+        assert_eq!(task.category, TaskCategory::Study);
+        assert_eq!(task.timer.target_duration, chrono::Duration::minutes(50));
+        assert!(task.priority == Priority::High);
+
+        let command = Command::AddTask {
+            description: "Read".to_string(),
+            category: Some("Reading".to_string()),
+            minutes: None,
+            priority: None,
+        };
+        process_command(&mut app, command);
+        let reading = TaskCategory::Other("Reading".to_string());
+        assert_eq!(app.tasks[1].category, reading);
+        assert!(app.tasks[1].priority == Priority::Medium);
+
+        let command = Command::AddTask {
+            description: "Bad".to_string(),
+            category: None,
+            minutes: None,
+            priority: Some("someday".to_string()),
+        };
+        match process_command(&mut app, command) {
+            Response::Error(e) => assert_eq!(e, "unknown priority 'someday'"),
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert_eq!(app.tasks.len(), 2);
+    }
+
+    #[test]
+    fn filter_keeps_tasks_short_of_their_pomodoro_estimate() {
+        let mut app = test_app();
+        for description in ["short", "done", "unestimated"] {
+            process_command(&mut app, add_task(description));
+        }
+        let setup = [(4, 50), (2, 50), (0, 30)];
+        for (task, (estimate, minutes)) in app.tasks.iter_mut().zip(setup) {
+            task.estimated_pomodoros = estimate;
+            task.timer.accumulated_time = chrono::Duration::minutes(minutes);
+        }
+
+        let filter = |app: &mut App, incomplete_pomodoros| {
+            let command = Command::FilterTasks {
+                incomplete_pomodoros,
+            };
+            let Response::Tasks(tasks) = process_command(app, command) else {
+                panic!("expected tasks");
+            };
+            tasks.into_iter().map(|t| t.description).collect::<Vec<_>>()
+        };
+        assert_eq!(filter(&mut app, true), ["short"]);
+        assert_eq!(filter(&mut app, false), ["short", "done", "unestimated"]);
+    }
+
+    #[tokio::test]
+    async fn read_message_joins_split_writes() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        let hello = serde_json::to_vec(&Command::Hello {
+            version: PROTOCOL_VERSION.to_string(),
+        })
+        .unwrap();
+        let (first, second) = hello.split_at(hello.len() / 2);
+        let mut buf = Vec::new();
+
+        client.write_all(first).await.unwrap();
+        let reading = read_message::<Command>(&mut server, &mut buf);
+        let writing = async {
+            tokio::task::yield_now().await;
+            client.write_all(second).await.unwrap();
+            // The next command follows in the same write, without a newline.
+            client.write_all(b"\n\"Ping\"").await.unwrap();
+        };
+        let (incoming, ()) = tokio::join!(reading, writing);
+        let Incoming::Message(Command::Hello { version }) = incoming.unwrap() else {
+            panic!("expected the handshake");
+        };
+        assert_eq!(version, PROTOCOL_VERSION);
+
+        drop(client);
+        let incoming = read_message::<Command>(&mut server, &mut buf)
+            .await
+            .unwrap();
+        assert!(matches!(incoming, Incoming::Message(Command::Ping)));
+        let incoming = read_message::<Command>(&mut server, &mut buf)
+            .await
+            .unwrap();
+        assert!(matches!(incoming, Incoming::Closed));
+    }
+
+    #[tokio::test]
+    async fn read_message_reports_a_cut_off_message() {
+        let mut wire: &[u8] = br#"{"Hello": {"vers"#;
+        let mut buf = Vec::new();
+        let incoming = read_message::<Command>(&mut wire, &mut buf).await.unwrap();
+        match incoming {
+            Incoming::Malformed(e) => assert_eq!(e, "connection closed mid-message"),
+            _ => panic!("expected a malformed message"),
+        }
+    }
+
+    #[test]
+    fn version_reports_the_crate_and_protocol() {
+        let mut app = test_app();
+        match process_command(&mut app, Command::Version) {
+            Response::Version { version, protocol } => {
+                assert_eq!(version, env!("CARGO_PKG_VERSION"));
+                assert_eq!(protocol, PROTOCOL_VERSION);
+            }
+            other => panic!("expected a version, got {:?}", other),
+        }
+        assert!(app.command_log.is_empty());
+    }
+
+    #[test]
+    fn presets_are_listed_and_applied_by_name() {
+        let mut app = test_app();
+        let Response::Presets(presets) = process_command(&mut app, Command::ListPresets) else {
+            panic!("expected presets");
+        };
+        assert_eq!(
+            presets,
+            [
+                ("Long Break".to_string(), 15),
+                ("Pomodoro".to_string(), 25),
+                ("Short Break".to_string(), 5),
+            ]
+        );
+
+        process_command(&mut app, add_task("Write report"));
+        let id = app.tasks[0].id;
+        let apply = |preset: &str| Command::ApplyPreset {
+            id,
+            preset: preset.to_string(),
+        };
+        assert!(matches!(
+            process_command(&mut app, apply("Short Break")),
+            Response::Ok
+        ));
+        assert_eq!(
+            app.tasks[0].timer.target_duration,
+            chrono::Duration::minutes(5)
+        );
+        assert!(matches!(
+            process_command(&mut app, apply("Deep Work")),
+            Response::Error(_)
+        ));
+        let missing = Command::ApplyPreset {
+            id: id + 1,
+            preset: "Pomodoro".to_string(),
+        };
+        assert!(matches!(
+            process_command(&mut app, missing),
+            Response::Error(_)
+        ));
+    }
+
+    #[test]
+    fn toggle_subtask_flips_a_valid_index_and_rejects_others() {
+        let mut app = test_app();
+        process_command(&mut app, add_task("Write report"));
+        let task_id = app.tasks[0].id;
+        app.split_task(0, vec!["outline".into(), "draft".into()], false);
+        let toggle = |subtask_index| Command::ToggleSubtask {
+            task_id,
+            subtask_index,
+        };
+
+        assert!(matches!(process_command(&mut app, toggle(1)), Response::Ok));
+        assert!(!app.tasks[0].subtasks[0].completed);
+        assert!(app.tasks[0].subtasks[1].completed);
+        process_command(&mut app, toggle(1));
+        assert!(!app.tasks[0].subtasks[1].completed);
+
+        let Response::Error(message) = process_command(&mut app, toggle(2)) else {
+            panic!("expected an error");
+        };
+        assert_eq!(
+            message,
+            format!("Task {} has no subtask 2 (it has 2)", task_id)
+        );
+        let missing = Command::ToggleSubtask {
+            task_id: task_id + 1,
+            subtask_index: 0,
+        };
+        assert!(matches!(
+            process_command(&mut app, missing),
+            Response::Error(_)
+        ));
+    }
+
+    #[test]
+    fn get_task_finds_one_task_by_id() {
+        let mut app = test_app();
+        process_command(&mut app, add_task("Write report"));
+        process_command(&mut app, add_task("Review PR"));
+        let id = app.tasks[1].id;
+
+        let Response::Task(task) = process_command(&mut app, Command::GetTask { id }) else {
+            panic!("expected a task");
+        };
+        assert_eq!(task.id, id);
+        assert_eq!(task.description, "Review PR");
+
+        let Response::Error(message) = process_command(&mut app, Command::GetTask { id: 99 })
+        else {
+            panic!("expected an error");
+        };
+        assert_eq!(message, "No task with id 99");
+    }
+}
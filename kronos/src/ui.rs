@@ -1,12 +1,25 @@
-use crate::app::{App, AppMode};
+use crate::app::{App, AppMode, AverageComparison, Priority, Stats, Timer};
+use crate::config::{color_to_hex, Features, Icons, Theme};
+use crate::keys::keybindings;
+use chrono::Timelike;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, Gauge, List, ListItem, Paragraph, Table},
+    widgets::{
+        Axis, Block, Borders, Chart, Clear, Dataset, Gauge, GraphType, List, ListItem, Paragraph,
+        Table,
+    },
     Frame,
 };
 use tachyonfx::{Duration as TachyonDuration, EffectRenderer};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Smallest terminal that fits the header, global timer, one task row and
+/// the status bar.
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 12;
 
 #[derive(Default, Clone)]
 pub struct UiLayout {
@@ -18,34 +31,84 @@ impl EffectRenderer<u32> for UiLayout {
     fn render_effect(&mut self, _key: &mut u32, _area: Rect, _delta: TachyonDuration) {}
 }
 
+pub fn render_too_small(area: Rect) -> bool {
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
+}
+
+/// Rows for the header, global timer, task list and status bar.
+fn main_constraints(show_global_timer: bool) -> [Constraint; 4] {
+    [
+        Constraint::Length(3),
+        // Hidden, the pane's rows go to the task list.
+        Constraint::Length(if show_global_timer { 5 } else { 0 }),
+        Constraint::Min(1),
+        Constraint::Length(3),
+    ]
+}
+
 pub fn draw(f: &mut Frame, app: &mut App) -> UiLayout {
     let area = f.area();
+    if render_too_small(area) {
+        draw_too_small(f, area, app);
+        return UiLayout::default();
+    }
+    let show_global_timer = app.config.features.show_global_timer;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Length(5),
-            Constraint::Min(1),
-            Constraint::Length(3),
-        ])
+        .constraints(main_constraints(show_global_timer))
         .split(area);
 
     draw_header(f, chunks[0], app);
-    draw_global_timer(f, chunks[1], app);
+    if show_global_timer {
+        draw_global_timer(f, chunks[1], app);
+    }
     let task_rects = draw_tasks(f, chunks[2], app);
     draw_status_bar(f, chunks[3], app);
 
     match &app.mode {
-        AppMode::AddingTask => draw_input_overlay(f, "New Task", &app.input_buffer, app),
+        AppMode::AddingTask | AppMode::InsertingTask(_) => {
+            draw_input_overlay(f, "New Task", &app.input_buffer, app)
+        }
         AppMode::EditingTime(_) => {
             draw_input_overlay(f, "Set Timer (minutes)", &app.input_buffer, app)
         }
-        AppMode::SelectingPreset(_) => draw_preset_overlay(f, app),
+        AppMode::EditingEstimate(_) => {
+            draw_input_overlay(f, "Estimated Pomodoros", &app.input_buffer, app)
+        }
+        AppMode::RenamingTask(_) => draw_input_overlay(f, "Rename Task", &app.input_buffer, app),
+        AppMode::EditingDue(_) => draw_input_overlay(
+            f,
+            "Due Date (YYYY-MM-DD, empty to clear)",
+            &app.input_buffer,
+            app,
+        ),
+        AppMode::SplittingTask(_) => {
+            let title = format!(
+                "Subtask {} (empty line to finish)",
+                app.split_lines.len() + 1
+            );
+            draw_input_overlay(f, &title, &app.input_buffer, app)
+        }
+        AppMode::SelectingPreset(_) => draw_preset_overlay(f, " Select Preset ", app),
+        AppMode::SelectingGlobalPreset => draw_preset_overlay(f, " Global Timer Preset ", app),
         AppMode::SelectingCategory(_) => draw_category_overlay(f, app),
+        AppMode::ManagingPresets => draw_preset_manager_overlay(f, app),
+        AppMode::EditingPresetName(_) => {
+            draw_input_overlay(f, "Preset Name", &app.input_buffer, app)
+        }
+        AppMode::EditingPresetMinutes(name) => {
+            let title = format!("{} (minutes)", name);
+            draw_input_overlay(f, &title, &app.input_buffer, app)
+        }
+        AppMode::ShowDetail(task_idx) => draw_detail_overlay(f, app, *task_idx),
         AppMode::ShowStats => draw_stats_overlay(f, app),
         AppMode::ShowHelp => draw_help_overlay(f, app),
+        AppMode::EditingTheme => draw_theme_editor_overlay(f, app),
         _ => {}
     }
+    if let Some(message) = app.active_toast(std::time::Instant::now()) {
+        draw_toast(f, chunks[2], message, app);
+    }
 
     UiLayout {
         tasks: task_rects,
@@ -53,9 +116,68 @@ pub fn draw(f: &mut Frame, app: &mut App) -> UiLayout {
     }
 }
 
+/// Small box in the top-right corner of `area`.
+fn draw_toast(f: &mut Frame, area: Rect, message: &str, app: &App) {
+    let width = (display_width(message) as u16 + 4).min(area.width);
+    let toast_area = Rect {
+        x: area.right().saturating_sub(width),
+        y: area.y,
+        width,
+        height: 3.min(area.height),
+    };
+    f.render_widget(Clear, toast_area);
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            message.to_string(),
+            Style::default().fg(app.config.theme.green),
+        ))
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(app.config.borders.overlays)
+                .border_style(Style::default().fg(app.config.theme.green)),
+        ),
+        toast_area,
+    );
+}
+
+fn draw_too_small(f: &mut Frame, area: Rect, app: &App) {
+    let lines = vec![
+        Line::from(Span::styled(
+            "Terminal too small",
+            Style::default()
+                .fg(app.config.theme.red)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format!(
+                "{}x{} < {}x{}",
+                area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+            ),
+            Style::default().fg(app.config.theme.gray),
+        )),
+    ];
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let message_area = Rect {
+        y: area.y + top,
+        height: area.height - top,
+        ..area
+    };
+    f.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        message_area,
+    );
+}
+
 fn draw_header(f: &mut Frame, area: Rect, app: &App) {
     let theme = &app.config.theme;
     let icons = &app.config.icons;
+    let remaining = app.total_remaining();
+    let completed = match app.config.features.daily_task_goal {
+        Some(goal) => format!("{}/{}", app.stats.daily_goal_progress(goal).0, goal),
+        None => app.stats.completed_today().to_string(),
+    };
     let text = Line::from(vec![
         Span::raw(icons.header_left.clone()),
         Span::styled(
@@ -63,6 +185,23 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
             Style::default().fg(theme.blue).add_modifier(Modifier::BOLD),
         ),
         Span::raw(icons.header_right.clone()),
+        Span::styled(
+            format!("  {} {} today", icons.done, completed),
+            Style::default().fg(theme.green),
+        ),
+        Span::styled(
+            format!("  {} {}", icons.streak, app.stats.daily_streak),
+            Style::default().fg(theme.magenta),
+        ),
+        Span::styled(
+            format!(
+                "  {} {}h{:02}m left",
+                icons.time_left,
+                remaining.num_hours(),
+                remaining.num_minutes() % 60
+            ),
+            Style::default().fg(theme.cyan),
+        ),
     ]);
     f.render_widget(
         Paragraph::new(text).alignment(Alignment::Center).block(
@@ -78,29 +217,51 @@ fn draw_global_timer(f: &mut Frame, area: Rect, app: &App) {
     let theme = &app.config.theme;
     let icons = &app.config.icons;
     let timer = &app.global_timer;
-    let remaining = timer.get_remaining();
+    let (remaining, prefix) = shown_time(timer);
     let time_str = if app.config.features.show_seconds {
         format!(
-            "{:02}:{:02}:{:02}",
+            "{}{:02}:{:02}:{:02}",
+            prefix,
             remaining.num_hours(),
             remaining.num_minutes() % 60,
             remaining.num_seconds() % 60
         )
+    } else if app.config.features.show_hours && remaining.num_minutes() >= 60 {
+        format!(
+            "{}{}",
+            prefix,
+            format_minutes(remaining.num_minutes(), true)
+        )
     } else {
         format!(
-            "{:02}:{:02}",
+            "{}{:02}:{:02}",
+            prefix,
             remaining.num_hours() * 60 + remaining.num_minutes(),
             remaining.num_seconds() % 60
         )
     };
-    let block = Block::default()
+    let mut block = Block::default()
         .title(Span::styled(
             format!(" {} Global ", icons.global_timer),
             Style::default().fg(theme.gray),
         ))
         .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
+        .border_type(app.config.borders.panes)
         .border_style(Style::default().fg(theme.green));
+    if app.config.features.show_today_total {
+        let worked = Stats::worked_today(&app.tasks);
+        block = block.title_bottom(
+            Line::from(Span::styled(
+                format!(
+                    " today {}h{:02}m ",
+                    worked.num_hours(),
+                    worked.num_minutes() % 60
+                ),
+                Style::default().fg(theme.gray),
+            ))
+            .right_aligned(),
+        );
+    }
     let inner_area = block.inner(area);
     f.render_widget(block, area);
     let v_chunks = Layout::default()
@@ -118,61 +279,215 @@ fn draw_global_timer(f: &mut Frame, area: Rect, app: &App) {
             .alignment(Alignment::Center),
         v_chunks[0],
     );
+    if timer.is_open_ended() {
+        f.render_widget(
+            Paragraph::new(OPEN_ENDED_LABEL)
+                .style(Style::default().fg(theme.gray))
+                .alignment(Alignment::Center),
+            v_chunks[1],
+        );
+        return;
+    }
     f.render_widget(
         Gauge::default()
-            .gauge_style(Style::default().fg(theme.blue).bg(theme.black))
+            .gauge_style(
+                Style::default()
+                    .fg(progress_color(
+                        timer.get_progress(),
+                        theme,
+                        app.config.features.progress_warn_at,
+                    ))
+                    .bg(theme.black),
+            )
             .percent((timer.get_progress() * 100.0) as u16),
         v_chunks[1],
     );
 }
 
+/// Shown in place of a progress bar for a timer with no target.
+const OPEN_ENDED_LABEL: &str = "open-ended";
+
+/// A task row's ten-cell progress bar, or the open-ended label for a timer
+/// with no target.
+fn task_progress_bar(timer: &Timer, icons: &Icons) -> String {
+    if timer.is_open_ended() {
+        return OPEN_ENDED_LABEL.to_string();
+    }
+    let filled = (timer.get_progress() * 10.0) as usize;
+    format!(
+        "{}{}",
+        icons.progress_filled.repeat(filled),
+        icons.progress_empty.repeat(10 - filled)
+    )
+}
+
+/// Whole minutes as `45m`, or with `hours` as `1h 30m` from an hour up.
+fn format_minutes(minutes: i64, hours: bool) -> String {
+    if hours && minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{:02}m", minutes)
+    }
+}
+
+/// `Timer::shown_time` with the arrow that marks a timer counting up.
+fn shown_time(timer: &Timer) -> (chrono::Duration, &'static str) {
+    match timer.shown_time() {
+        (shown, true) => (shown, "↑"),
+        (shown, false) => (shown, ""),
+    }
+}
+
+/// A task row's time: `MM:SS` with `show_seconds`, whole minutes otherwise.
+fn task_timer_text(timer: &Timer, features: &Features) -> String {
+    let (rem, prefix) = shown_time(timer);
+    if features.show_seconds {
+        format!(
+            "{}{:02}:{:02}",
+            prefix,
+            rem.num_minutes().max(0),
+            (rem.num_seconds() % 60).max(0)
+        )
+    } else {
+        format!(
+            "{}{}",
+            prefix,
+            format_minutes(rem.num_minutes().max(0), features.show_hours)
+        )
+    }
+}
+
 fn draw_tasks(f: &mut Frame, area: Rect, app: &App) -> Vec<Rect> {
     let theme = &app.config.theme;
     let icons = &app.config.icons;
+    let pomodoro_minutes = app.pomodoro_minutes();
+    let title = if app.show_only_active {
+        format!(
+            " {} Tasks · {} active ",
+            icons.task_list,
+            app.active_task_indices().len()
+        )
+    } else {
+        format!(" {} Tasks ", icons.task_list)
+    };
     let block = Block::default()
-        .title(Span::styled(
-            format!(" {} Tasks ", icons.task_list),
-            Style::default().fg(theme.gray),
-        ))
+        .title(Span::styled(title, Style::default().fg(theme.gray)))
         .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
+        .border_type(app.config.borders.panes)
         .border_style(Style::default().fg(theme.green));
     let inner_area = block.inner(area);
     f.render_widget(block, area);
 
     if app.tasks.is_empty() {
         f.render_widget(
-            Paragraph::new("No tasks. Press 'a' to add one.")
+            Paragraph::new(vec![
+                Line::from("No tasks. Press 'a' to add one."),
+                Line::from(""),
+                Line::from(Span::styled(
+                    app.current_tip(),
+                    Style::default().add_modifier(Modifier::ITALIC),
+                )),
+            ])
+            .style(Style::default().fg(theme.gray))
+            .alignment(Alignment::Center),
+            inner_area,
+        );
+        return vec![];
+    }
+
+    // Hidden tasks keep an empty rect so the result still lines up with
+    // `app.tasks`.
+    let visible: Vec<usize> = (0..app.tasks.len())
+        .filter(|&i| app.is_task_visible(i))
+        .collect();
+    if visible.is_empty() {
+        f.render_widget(
+            Paragraph::new("No tasks match the current filter.")
                 .style(Style::default().fg(theme.gray))
                 .alignment(Alignment::Center),
             inner_area,
         );
-        return vec![];
+    }
+    let constraints: Vec<Constraint> = visible.iter().map(|_| Constraint::Length(1)).collect();
+    let visible_chunks = Layout::default().constraints(constraints).split(inner_area);
+    let mut task_chunks = vec![Rect::default(); app.tasks.len()];
+    for (&i, &chunk) in visible.iter().zip(visible_chunks.iter()) {
+        task_chunks[i] = chunk;
     }
 
-    let constraints: Vec<Constraint> = app.tasks.iter().map(|_| Constraint::Length(1)).collect();
-    let task_chunks = Layout::default().constraints(constraints).split(inner_area);
+    let compact = app.compact_view || inner_area.width < app.config.features.compact_width;
+    let today = chrono::Local::now().date_naive();
 
-    for (i, task) in app.tasks.iter().enumerate() {
+    for &i in &visible {
+        let task = &app.tasks[i];
         if let Some(item_area) = task_chunks.get(i) {
-            let mut left = vec![if i == app.selected_task {
-                Span::styled(
-                    icons.select.clone(),
-                    Style::default().fg(theme.selection),
+            let state_icon = match task.timer.state {
+                kronos_ipc::TimerState::Running => &icons.play,
+                kronos_ipc::TimerState::Paused => &icons.pause,
+                kronos_ipc::TimerState::Idle => &icons.stop,
+            };
+
+            let timer_txt = task_timer_text(&task.timer, &app.config.features);
+
+            let (right_txt, bar) = if compact {
+                (format!(" {} {} ", state_icon, timer_txt), String::new())
+            } else {
+                let bar = task_progress_bar(&task.timer, icons);
+                let pomodoros = if task.estimated_pomodoros > 0 {
+                    format!(
+                        "{}/{} {} ",
+                        task.completed_pomodoros(pomodoro_minutes),
+                        task.estimated_pomodoros,
+                        icons.pomodoro
+                    )
+                } else {
+                    String::new()
+                };
+                (
+                    format!(" {}{} {} ", pomodoros, state_icon, timer_txt),
+                    format!("{} ", bar),
                 )
+            };
+
+            let accent = priority_color(&task.priority, theme);
+            let mut left = vec![if i == app.selected_task {
+                Span::styled(icons.select.clone(), Style::default().fg(accent))
             } else {
                 Span::raw(" ")
             }];
-            left.push(Span::raw(format!(
+            let status_txt = format!(
                 " {} ",
                 if task.completed {
-                    &icons.done
+                    app.done_icon(task.id)
                 } else {
                     &icons.pending
                 }
-            )));
+            );
+            let category_txt = if compact {
+                String::new()
+            } else {
+                format!(" ({})", task.category)
+            };
+            let deferred_txt = match task.deferred_until {
+                Some(until) if task.is_deferred_on(today) => {
+                    format!(" {} {}", icons.deferred, until.format("%b %d"))
+                }
+                _ => String::new(),
+            };
+            // Keep the description clear of the right-aligned timer column.
+            let used = 1
+                + display_width(&status_txt)
+                + display_width(&category_txt)
+                + display_width(&deferred_txt)
+                + display_width(&right_txt)
+                + display_width(&bar);
+            let description = truncate_with_ellipsis(
+                &task.description,
+                (item_area.width as usize).saturating_sub(used),
+            );
+            left.push(Span::raw(status_txt));
             left.push(Span::styled(
-                task.description.clone(),
+                description,
                 if task.completed {
                     Style::default()
                         .fg(theme.gray)
@@ -181,41 +496,23 @@ fn draw_tasks(f: &mut Frame, area: Rect, app: &App) -> Vec<Rect> {
                     Style::default().fg(theme.foreground)
                 },
             ));
-            left.push(Span::styled(
-                format!(" ({})", task.category.to_string()),
-                Style::default().fg(theme.yellow),
-            ));
-
-            let state_icon = match task.timer.state {
-                kronos_ipc::TimerState::Running => &icons.play,
-                kronos_ipc::TimerState::Paused => &icons.pause,
-                kronos_ipc::TimerState::Idle => &icons.stop,
-            };
-
-            let rem = task.timer.get_remaining();
-            let timer_txt = if app.config.features.show_seconds {
-                format!(
-                    "{:02}:{:02}",
-                    rem.num_minutes().max(0),
-                    (rem.num_seconds() % 60).max(0)
-                )
-            } else {
-                format!("{:02}m", rem.num_minutes().max(0))
-            };
+            if !compact {
+                left.push(Span::styled(
+                    category_txt,
+                    Style::default().fg(theme.yellow),
+                ));
+            }
+            left.push(Span::styled(deferred_txt, Style::default().fg(theme.gray)));
 
-            let bar = format!(
-                "{}{}",
-                icons
-                    .progress_filled
-                    .repeat((task.timer.get_progress() * 10.0) as usize),
-                icons
-                    .progress_empty
-                    .repeat(10 - (task.timer.get_progress() * 10.0) as usize)
-            );
-            let right = Span::styled(
-                format!(" {} {} {} ", state_icon, timer_txt, bar),
-                Style::default().fg(theme.cyan),
+            let bar_color = progress_color(
+                task.timer.get_progress(),
+                theme,
+                app.config.features.progress_warn_at,
             );
+            let right = Line::from(vec![
+                Span::styled(right_txt, Style::default().fg(accent)),
+                Span::styled(bar, Style::default().fg(bar_color)),
+            ]);
 
             if i == app.selected_task {
                 f.render_widget(
@@ -226,32 +523,138 @@ fn draw_tasks(f: &mut Frame, area: Rect, app: &App) -> Vec<Rect> {
 
             f.render_widget(Paragraph::new(Line::from(left)), *item_area);
             f.render_widget(
-                Paragraph::new(Line::from(right)).alignment(Alignment::Right),
+                Paragraph::new(right).alignment(Alignment::Right),
                 *item_area,
             );
         }
     }
 
-    task_chunks.to_vec()
+    task_chunks
+}
+
+/// Accent for a task row's selection marker and timer text.
+fn priority_color(priority: &Priority, theme: &Theme) -> Color {
+    match priority {
+        Priority::Urgent => theme.red,
+        Priority::High => theme.yellow,
+        Priority::Medium => theme.cyan,
+        Priority::Low => theme.gray,
+    }
+}
+
+/// Green while there's plenty of time left, yellow from `warn_at`, red once
+/// the timer has run out.
+fn progress_color(progress: f64, theme: &Theme, warn_at: f64) -> Color {
+    if progress >= 1.0 {
+        theme.red
+    } else if progress >= warn_at {
+        theme.yellow
+    } else {
+        theme.green
+    }
+}
+
+/// Number of terminal columns `text` takes up. Wide CJK characters and most
+/// emoji count as two.
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Shortens `text` to at most `width` columns, marking the cut with an
+/// ellipsis.
+pub fn truncate_with_ellipsis(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for ch in text.chars() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if used + ch_width > width - 1 {
+            break;
+        }
+        used += ch_width;
+        truncated.push(ch);
+    }
+    truncated.push('…');
+    truncated
 }
 
 fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
     let theme = &app.config.theme;
     let (mode_text, mode_color) = match app.mode {
         AppMode::Normal => ("NORMAL", theme.green),
-        AppMode::AddingTask => ("INSERT", theme.yellow),
+        AppMode::AddingTask | AppMode::InsertingTask(_) => ("INSERT", theme.yellow),
         AppMode::EditingTime(_) => ("TIME", theme.blue),
-        AppMode::SelectingPreset(_) => ("PRESET", theme.magenta),
+        AppMode::EditingEstimate(_) => ("ESTIMATE", theme.blue),
+        AppMode::SelectingPreset(_) | AppMode::SelectingGlobalPreset => ("PRESET", theme.magenta),
         AppMode::SelectingCategory(_) => ("CATEGORY", theme.cyan),
+        AppMode::ManagingPresets
+        | AppMode::EditingPresetName(_)
+        | AppMode::EditingPresetMinutes(_) => ("PRESETS", theme.magenta),
+        AppMode::ShowDetail(_) => ("DETAIL", theme.magenta),
         AppMode::ShowStats => ("STATS", theme.magenta),
         AppMode::ShowHelp => ("HELP", theme.magenta),
+        AppMode::EditingTheme => ("THEME", theme.magenta),
+        AppMode::ConfirmingMerge => ("MERGE", theme.red),
+        AppMode::SplittingTask(_) => ("SPLIT", theme.yellow),
+        AppMode::RenamingTask(_) => ("RENAME", theme.yellow),
+        AppMode::EditingDue(_) => ("DUE", theme.blue),
         AppMode::StartupAnimation => ("NORMAL", theme.magenta),
     };
 
     let help = match app.mode {
-        AppMode::Normal => "a:add | d:del | x:done | t:time | p:preset | c:cat | r:reset | s:stats | gG:global timer | ?:help | q:quit",
+        AppMode::Normal => "a:add | o/O:add below/above | y:dup | d:del | x:done | i:info | R:rename | w:due | t:time | e:estimate | p:preset | P:presets | c:cat | f:cycle cat | b:split | n:suggest | v:compact | >:defer | D:deferred | A:active only | M:merge dups | ::seconds | m:motion | S:stop | r:reset | u/C-r:undo/redo | l:lap | z:snooze | s:stats | gG:global timer | T:global preset | K:keys | ?:help | q:quit",
+        AppMode::ManagingPresets => "a:add | r:rename | t:minutes | d:delete | esc:back",
+        AppMode::EditingTheme => "j/k:color | r/g/b:channel | h/l:nudge | enter:save | esc:revert",
+        AppMode::SelectingPreset(_) | AppMode::SelectingGlobalPreset => {
+            "type:filter | 1-9:pick | enter:best match | esc:clear/cancel"
+        }
+        AppMode::SelectingCategory(_) => "type:filter | ↑↓:move | enter:confirm | esc:clear/cancel",
+        AppMode::ConfirmingMerge => "y:merge | any other key:cancel",
+        AppMode::SplittingTask(_) => "enter:next subtask | enter on empty line:finish | esc:cancel",
         _ => "enter:confirm | esc:cancel",
     };
+    let message = app
+        .status_message
+        .as_ref()
+        .map(|m| format!("{} | ", m))
+        .unwrap_or_default();
+    let icons = &app.config.icons;
+    let mut hints = String::new();
+    let now = chrono::Local::now().time();
+    if app.config.features.in_quiet_hours(now) {
+        hints.push_str(&format!("{} quiet | ", icons.quiet));
+    }
+    if app.break_suggested {
+        hints.push_str(&format!("{} time for a break | ", icons.break_due));
+    }
+    if app.long_break_due() {
+        hints.push_str(&format!("{} long break due | ", icons.long_break_due));
+    }
+
+    let bar_style = Style::default().bg(theme.black).fg(theme.gray);
+    let area = if app.config.features.status_bar_stats {
+        let summary = format!(" {} ", app.stats.status_summary(icons));
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(display_width(&summary) as u16),
+            ])
+            .split(area);
+        f.render_widget(
+            Paragraph::new(Span::styled(summary, Style::default().fg(theme.magenta)))
+                .style(bar_style),
+            chunks[1],
+        );
+        chunks[0]
+    } else {
+        area
+    };
 
     f.render_widget(
         Paragraph::new(Line::from(vec![
@@ -263,21 +666,30 @@ fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" "),
+            Span::styled(message, Style::default().fg(theme.red)),
+            Span::styled(hints, Style::default().fg(theme.yellow)),
             Span::raw(help),
         ]))
-        .block(Block::default().style(Style::default().bg(theme.black).fg(theme.gray))),
+        .block(Block::default().style(bar_style)),
         area,
     );
 }
 
 fn draw_input_overlay(f: &mut Frame, title: &str, input: &str, app: &App) {
-    let area = centered_rect(60, 60, f.area());
+    let features = &app.config.features;
+    let area = centered_rect_bounded(
+        60,
+        60,
+        features.overlay_max_width,
+        features.overlay_max_height,
+        f.area(),
+    );
     f.render_widget(Clear, area);
     let block = Block::default()
         .title(format!(" {} ", title))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(app.config.theme.yellow))
-        .border_type(BorderType::Double)
+        .border_type(app.config.borders.overlays)
         .style(Style::default().bg(app.config.theme.background));
     let inner_area = block.inner(area);
     f.render_widget(block, area);
@@ -297,14 +709,22 @@ fn draw_input_overlay(f: &mut Frame, title: &str, input: &str, app: &App) {
     );
 }
 
-fn draw_preset_overlay(f: &mut Frame, app: &App) {
+fn draw_preset_overlay(f: &mut Frame, title: &str, app: &App) {
     let area = centered_rect(50, 40, f.area());
     f.render_widget(Clear, area);
     let items: Vec<ListItem> = app
-        .get_preset_names()
+        .filtered_preset_names()
         .iter()
         .enumerate()
         .map(|(i, name)| {
+            // The first entry is the best match, which Enter picks.
+            let style = if i == 0 {
+                Style::default()
+                    .bg(app.config.theme.selection)
+                    .fg(app.config.theme.background)
+            } else {
+                Style::default()
+            };
             ListItem::new(Line::from(vec![
                 Span::styled(
                     format!("{}. ", i + 1),
@@ -316,26 +736,128 @@ fn draw_preset_overlay(f: &mut Frame, app: &App) {
                     Style::default().fg(app.config.theme.gray),
                 ),
             ]))
+            .style(style)
         })
         .collect();
 
     f.render_widget(
         List::new(items).block(
             Block::default()
-                .title(" Select Preset ")
+                .title(picker_title(title, &app.input_buffer))
                 .borders(Borders::ALL)
-                .border_type(BorderType::Double)
+                .border_type(app.config.borders.overlays)
                 .border_style(Style::default().fg(app.config.theme.magenta)),
         ),
         area,
     );
 }
 
+fn draw_theme_editor_overlay(f: &mut Frame, app: &App) {
+    // Kept small so most of the UI stays visible as a live preview.
+    let area = centered_rect_bounded(40, 80, 36, 15, f.area());
+    f.render_widget(Clear, area);
+    let theme = &app.config.theme;
+    let editor = &app.theme_editor;
+
+    let mut lines: Vec<Line> = Theme::FIELDS
+        .iter()
+        .zip(theme.colors())
+        .enumerate()
+        .map(|(i, (name, color))| {
+            let marker = if i == editor.field {
+                app.config.icons.select.clone()
+            } else {
+                " ".to_string()
+            };
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.selection)),
+                Span::raw(format!(" {:<11}", name)),
+                Span::styled("    ", Style::default().bg(color)),
+                Span::raw(format!(" {}", color_to_hex(color).unwrap_or_default())),
+            ])
+        })
+        .collect();
+
+    if let Some(Color::Rgb(r, g, b)) = theme.colors().get(editor.field).copied() {
+        let channels = [("R", r), ("G", g), ("B", b)];
+        let mut spans = vec![Span::raw("  ")];
+        for (i, (label, value)) in channels.iter().enumerate() {
+            let style = if i == editor.channel {
+                Style::default()
+                    .fg(theme.selection)
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            } else {
+                Style::default().fg(theme.gray)
+            };
+            spans.push(Span::styled(format!("{} {:>3}", label, value), style));
+            spans.push(Span::raw("  "));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(spans));
+    }
+
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(" Theme ")
+                .borders(Borders::ALL)
+                .border_type(app.config.borders.overlays)
+                .border_style(Style::default().fg(theme.magenta))
+                .style(Style::default().bg(theme.background)),
+        ),
+        area,
+    );
+}
+
+fn draw_preset_manager_overlay(f: &mut Frame, app: &mut App) {
+    let area = centered_rect(50, 40, f.area());
+    f.render_widget(Clear, area);
+    let items: Vec<ListItem> = app
+        .get_preset_names()
+        .iter()
+        .map(|name| {
+            ListItem::new(Line::from(vec![
+                Span::raw(name.clone()),
+                Span::styled(
+                    format!(" ({}m)", app.presets.get(name).unwrap_or(&0)),
+                    Style::default().fg(app.config.theme.gray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(" Manage Presets ")
+                .borders(Borders::ALL)
+                .border_type(app.config.borders.overlays)
+                .border_style(Style::default().fg(app.config.theme.magenta)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(app.config.theme.selection)
+                .fg(app.config.theme.background),
+        )
+        .highlight_symbol(&app.config.icons.select);
+
+    f.render_stateful_widget(list, area, &mut app.preset_list_state);
+}
+
+/// Appends the fuzzy-filter query, if any, to a picker's title.
+fn picker_title(title: &str, query: &str) -> String {
+    if query.is_empty() {
+        title.to_string()
+    } else {
+        format!("{}[{}] ", title, query)
+    }
+}
+
 fn draw_category_overlay(f: &mut Frame, app: &mut App) {
     let area = centered_rect(50, 40, f.area());
     f.render_widget(Clear, area);
     let items: Vec<ListItem> = app
-        .get_category_names()
+        .filtered_category_names()
         .iter()
         .map(|name| ListItem::new(Line::from(vec![Span::raw(name.clone())])))
         .collect();
@@ -343,9 +865,9 @@ fn draw_category_overlay(f: &mut Frame, app: &mut App) {
     let list = List::new(items)
         .block(
             Block::default()
-                .title(" Select Category ")
+                .title(picker_title(" Select Category ", &app.input_buffer))
                 .borders(Borders::ALL)
-                .border_type(BorderType::Double)
+                .border_type(app.config.borders.overlays)
                 .border_style(Style::default().fg(app.config.theme.cyan)),
         )
         .highlight_style(
@@ -358,6 +880,146 @@ fn draw_category_overlay(f: &mut Frame, app: &mut App) {
     f.render_stateful_widget(list, area, &mut app.category_list_state);
 }
 
+fn draw_detail_overlay(f: &mut Frame, app: &App, task_idx: usize) {
+    let Some(task) = app.tasks.get(task_idx) else {
+        return;
+    };
+    let theme = &app.config.theme;
+    let features = &app.config.features;
+    let area = centered_rect(70, 80, f.area());
+    f.render_widget(Clear, area);
+
+    let field = |name: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(name, Style::default().fg(theme.blue)),
+            Span::raw(value),
+        ])
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            task.description.clone(),
+            Style::default()
+                .fg(theme.foreground)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        field("Category: ", task.category.to_string()),
+        field("Priority: ", task.priority.to_string()),
+        field(
+            "Due: ",
+            task.due
+                .map_or("-".to_string(), |due| due.format("%b %d").to_string()),
+        ),
+        field(
+            "Time: ",
+            format!(
+                "{}m of {}m",
+                task.timer.get_elapsed().num_minutes(),
+                task.timer.target_duration.num_minutes()
+            ),
+        ),
+        field(
+            "Last lap: ",
+            task.timer.laps.last().map_or("-".to_string(), |lap| {
+                format!(
+                    "{:02}:{:02} ({} total)",
+                    lap.num_minutes(),
+                    lap.num_seconds() % 60,
+                    task.timer.laps.len()
+                )
+            }),
+        ),
+        field(
+            "Vs average: ",
+            match (
+                app.stats.average_time_per_completed(),
+                app.stats.compare_to_average(task.timer.get_elapsed()),
+            ) {
+                (Some(average), Some(comparison)) => format!(
+                    "{} ({}m per completed task)",
+                    match comparison {
+                        AverageComparison::Below => "below average",
+                        AverageComparison::Near => "near average",
+                        AverageComparison::Above => "above average",
+                    },
+                    average.num_minutes()
+                ),
+                _ => "-".to_string(),
+            },
+        ),
+        field("Created: ", features.format_datetime(task.created_at)),
+        field(
+            "Completed: ",
+            task.completed_at
+                .map_or("-".to_string(), |d| features.format_datetime(d)),
+        ),
+        Line::from(""),
+    ];
+    if !task.subtasks.is_empty() {
+        let done = task.subtasks.iter().filter(|s| s.completed).count();
+        lines.push(Line::from(Span::styled(
+            format!("Subtasks ({}/{})", done, task.subtasks.len()),
+            Style::default().fg(theme.blue).add_modifier(Modifier::BOLD),
+        )));
+        for subtask in &task.subtasks {
+            let check = if subtask.completed { "✓" } else { " " };
+            let minutes = subtask
+                .target_minutes
+                .map(|m| format!(" ({}m)", m))
+                .unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(format!("  [{}] ", check), Style::default().fg(theme.green)),
+                Span::styled(
+                    subtask.description.clone(),
+                    Style::default().fg(theme.foreground),
+                ),
+                Span::styled(minutes, Style::default().fg(theme.gray)),
+            ]));
+        }
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "Sessions",
+        Style::default().fg(theme.blue).add_modifier(Modifier::BOLD),
+    )));
+    if task.timer.intervals.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  Not started yet",
+            Style::default().fg(theme.gray),
+        )));
+    }
+    for interval in &task.timer.intervals {
+        let (end, minutes) = match interval.end {
+            Some(end) => (
+                features.format_time(end),
+                (end - interval.start).num_minutes(),
+            ),
+            None => (
+                "now".to_string(),
+                (chrono::Local::now() - interval.start).num_minutes(),
+            ),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {} - {}", features.format_datetime(interval.start), end),
+                Style::default().fg(theme.foreground),
+            ),
+            Span::styled(format!(" ({}m)", minutes), Style::default().fg(theme.gray)),
+        ]));
+    }
+
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(" Task ")
+                .borders(Borders::ALL)
+                .border_type(app.config.borders.overlays)
+                .border_style(Style::default().fg(theme.magenta)),
+        ),
+        area,
+    );
+}
+
 fn draw_stats_overlay(f: &mut Frame, app: &App) {
     let area = centered_rect(70, 80, f.area());
     f.render_widget(Clear, area);
@@ -365,17 +1027,29 @@ fn draw_stats_overlay(f: &mut Frame, app: &App) {
     let block = Block::default()
         .title(" Statistics ")
         .borders(Borders::ALL)
-        .border_type(BorderType::Double)
+        .border_type(app.config.borders.overlays)
         .border_style(Style::default().fg(app.config.theme.magenta));
 
     let inner_area = block.inner(area);
     f.render_widget(block, area);
 
+    let goal_height = if app.config.features.weekly_goal_hours.is_some() {
+        2
+    } else {
+        0
+    };
     let stats_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(4), Constraint::Min(0)])
+        .constraints([
+            Constraint::Length(8),
+            Constraint::Length(goal_height),
+            Constraint::Length(8),
+            Constraint::Min(0),
+        ])
         .split(inner_area);
 
+    let (pomodoros_done, pomodoros_planned) = app.pomodoro_totals();
+    let backlog_change = Stats::net_backlog_change(&app.tasks, chrono::Local::now().date_naive());
     let summary_text = vec![
         Line::from(vec![
             Span::styled("Tasks Completed: ", Style::default().fg(app.config.theme.blue)),
@@ -389,22 +1063,63 @@ fn draw_stats_overlay(f: &mut Frame, app: &App) {
             Span::styled("Daily Streak: ", Style::default().fg(app.config.theme.blue)),
             Span::raw(format!("{} days", app.stats.daily_streak)),
         ]),
+        Line::from(vec![
+            Span::styled("Pomodoros: ", Style::default().fg(app.config.theme.blue)),
+            Span::raw(format!("{}/{} done", pomodoros_done, pomodoros_planned)),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "Backlog Today: ",
+                Style::default().fg(app.config.theme.blue),
+            ),
+            Span::raw(format!("{:+} tasks", backlog_change)),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "Sessions Today: ",
+                Style::default().fg(app.config.theme.blue),
+            ),
+            Span::raw(app.stats.work_sessions_today().to_string()),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "Longest Focus: ",
+                Style::default().fg(app.config.theme.blue),
+            ),
+            Span::raw(format!(
+                "{}m (today {}m)",
+                app.stats.longest_focus_session.num_minutes(),
+                app.stats.longest_focus_session_today().num_minutes()
+            )),
+        ]),
     ];
 
     f.render_widget(Paragraph::new(summary_text), stats_chunks[0]);
+    if let Some(goal) = app.config.features.weekly_goal_hours {
+        draw_weekly_goal(f, stats_chunks[1], app, goal);
+    }
+    draw_burndown(f, stats_chunks[2], app);
 
-    let category_rows = app.stats.tasks_by_category.iter().map(|(category, count)| {
-        ratatui::widgets::Row::new(vec![
-            category.to_string(),
-            count.to_string(),
-        ])
-    });
+    let category_rows = app
+        .stats
+        .sorted_categories(app.config.features.category_sort)
+        .into_iter()
+        .map(|(category, tasks, timers)| {
+            ratatui::widgets::Row::new(vec![category, tasks.to_string(), timers.to_string()])
+        });
 
     let category_table = Table::new(
         category_rows,
-        &[Constraint::Percentage(50), Constraint::Percentage(50)],
+        &[
+            Constraint::Percentage(50),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+        ],
+    )
+    .header(
+        ratatui::widgets::Row::new(vec!["Category", "Tasks", "Timers"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
     )
-    .header(ratatui::widgets::Row::new(vec!["Category", "Tasks"]).style(Style::default().add_modifier(Modifier::BOLD)))
     .block(
         Block::default()
             .title("Tasks by Category")
@@ -412,63 +1127,119 @@ fn draw_stats_overlay(f: &mut Frame, app: &App) {
             .border_style(Style::default().fg(app.config.theme.gray)),
     );
 
-    f.render_widget(category_table, stats_chunks[1]);
+    f.render_widget(category_table, stats_chunks[3]);
+}
+
+/// Today's planned minutes left, stepping down on completions and up when
+/// tasks are added.
+fn draw_burndown(f: &mut Frame, area: Rect, app: &App) {
+    let theme = &app.config.theme;
+    let now = chrono::Local::now();
+    let samples = Stats::burndown_points(&app.tasks, now.date_naive(), now);
+    let minute_of_day = |at: &chrono::DateTime<chrono::Local>| {
+        f64::from(at.time().num_seconds_from_midnight()) / 60.0
+    };
+    // Hold each value until the next sample so the line steps.
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    for (at, remaining) in &samples {
+        let x = minute_of_day(at);
+        if let Some(&(_, previous)) = points.last() {
+            points.push((x, previous));
+        }
+        points.push((x, *remaining as f64));
+    }
+    let start = points.first().map_or(0.0, |p| p.0);
+    let end = points.last().map_or(0.0, |p| p.0).max(start + 1.0);
+    let top = points.iter().map(|p| p.1).fold(1.0, f64::max);
+    let time_label = |minutes: f64| {
+        Span::raw(format!(
+            "{:02}:{:02}",
+            (minutes / 60.0) as u32,
+            (minutes % 60.0) as u32
+        ))
+    };
+
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(theme.cyan))
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    "Burndown Today (planned minutes left)",
+                    Style::default().fg(theme.blue),
+                ))
+                .borders(Borders::TOP)
+                .border_style(Style::default().fg(theme.gray)),
+        )
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.gray))
+                .bounds([start, end])
+                .labels(vec![time_label(start), time_label(end)]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.gray))
+                .bounds([0.0, top])
+                .labels(vec![Span::raw("0"), Span::raw(format!("{}m", top))]),
+        );
+    f.render_widget(chart, area);
+}
+
+/// Bar of this week's focus time against `goal` hours. The bar stops at
+/// 100%; the label shows any overage.
+fn draw_weekly_goal(f: &mut Frame, area: Rect, app: &App, goal: f64) {
+    let theme = &app.config.theme;
+    let now = chrono::Local::now();
+    let week_start = app.config.features.week_start;
+    let progress = Stats::weekly_goal_progress(&app.tasks, goal, week_start, now);
+    let worked = Stats::worked_this_week(&app.tasks, week_start, now).num_minutes() as f64 / 60.0;
+    let label = if progress > 1.0 {
+        format!(
+            "{:.1}h / {:.1}h ({:.0}%, +{:.1}h over)",
+            worked,
+            goal,
+            progress * 100.0,
+            worked - goal
+        )
+    } else {
+        format!("{:.1}h / {:.1}h ({:.0}%)", worked, goal, progress * 100.0)
+    };
+    f.render_widget(
+        Gauge::default()
+            .block(
+                Block::default()
+                    .title(Span::styled("Weekly Goal", Style::default().fg(theme.blue))),
+            )
+            .gauge_style(Style::default().fg(theme.green).bg(theme.black))
+            .ratio(progress.clamp(0.0, 1.0))
+            .label(label),
+        area,
+    );
 }
 
 fn draw_help_overlay(f: &mut Frame, app: &App) {
     let area = centered_rect(70, 80, f.area());
     f.render_widget(Clear, area);
 
-    let shortcuts = vec![
-        (
-            "General",
-            vec![
-                ("q", "Quit"),
-                ("s", "Show Stats"),
-                ("?", "Toggle help"),
-            ],
-        ),
-        (
-            "Tasks",
-            vec![
-                ("a", "Add task"),
-                ("d", "Delete task"),
-                ("x", "Toggle complete"),
-                ("Space", "Start/pause timer"),
-                ("r", "Reset timer"),
-                ("t", "Set time"),
-                ("p", "Select preset"),
-                ("c", "Change category"),
-            ],
-        ),
-        (
-            "Navigation",
-            vec![
-                ("j/↓", "Move down"),
-                ("k/↑", "Move up"),
-            ],
-        ),
-        (
-            "Global Timer",
-            vec![
-                ("g", "Start/pause global timer"),
-                ("G", "Reset global timer"),
-            ],
-        ),
-    ];
-
     let mut lines = vec![];
-    for (section, keys) in shortcuts {
+    for (section, keys) in keybindings() {
         lines.push(Line::from(Span::styled(
-            section,
+            *section,
             Style::default()
                 .fg(app.config.theme.blue)
                 .add_modifier(Modifier::BOLD),
         )));
-        for (key, desc) in keys {
+        for (key, desc) in keys.iter() {
             lines.push(Line::from(vec![
-                Span::styled(format!("  {:>6} : ", key), Style::default().fg(app.config.theme.yellow)),
-                Span::raw(desc),
+                Span::styled(
+                    format!("  {:>6} : ", key),
+                    Style::default().fg(app.config.theme.yellow),
+                ),
+                Span::raw(*desc),
             ]));
         }
         lines.push(Line::from(""));
@@ -479,7 +1250,7 @@ fn draw_help_overlay(f: &mut Frame, app: &App) {
             Block::default()
                 .title(" Help ")
                 .borders(Borders::ALL)
-                .border_type(BorderType::Double)
+                .border_type(app.config.borders.overlays)
                 .border_style(Style::default().fg(app.config.theme.magenta)),
         ),
         area,
@@ -505,3 +1276,159 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+/// Like `centered_rect`, but never larger than `max_w` x `max_h` cells.
+fn centered_rect_bounded(percent_x: u16, percent_y: u16, max_w: u16, max_h: u16, r: Rect) -> Rect {
+    let popup = centered_rect(percent_x, percent_y, r);
+    let width = popup.width.min(max_w);
+    let height = popup.height.min(max_h);
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn too_small_below_either_minimum() {
+        assert!(!render_too_small(Rect::new(0, 0, MIN_WIDTH, MIN_HEIGHT)));
+        assert!(render_too_small(Rect::new(0, 0, MIN_WIDTH - 1, MIN_HEIGHT)));
+        assert!(render_too_small(Rect::new(0, 0, MIN_WIDTH, MIN_HEIGHT - 1)));
+        assert!(!render_too_small(Rect::new(0, 0, 200, 60)));
+    }
+
+    #[test]
+    fn truncate_leaves_text_that_fits() {
+        assert_eq!(truncate_with_ellipsis("Write", 5), "Write");
+        assert_eq!(truncate_with_ellipsis("Write", 10), "Write");
+    }
+
+    #[test]
+    fn truncate_marks_the_cut_with_an_ellipsis() {
+        assert_eq!(truncate_with_ellipsis("Write report", 6), "Write…");
+        assert_eq!(truncate_with_ellipsis("Write report", 1), "…");
+        assert_eq!(truncate_with_ellipsis("Write report", 0), "");
+    }
+
+    #[test]
+    fn overlay_keeps_its_percentage_on_small_terminals() {
+        let area = Rect::new(0, 0, 40, 20);
+        assert_eq!(
+            centered_rect_bounded(60, 20, 80, 20, area),
+            centered_rect(60, 20, area)
+        );
+    }
+
+    #[test]
+    fn overlay_is_clamped_and_centered_on_large_terminals() {
+        let area = Rect::new(0, 0, 400, 100);
+        assert_eq!(
+            centered_rect_bounded(60, 50, 80, 20, area),
+            Rect::new(160, 40, 80, 20)
+        );
+    }
+
+    #[test]
+    fn progress_color_turns_yellow_then_red() {
+        let theme = Theme::default();
+        assert_eq!(progress_color(0.0, &theme, 0.8), theme.green);
+        assert_eq!(progress_color(0.79, &theme, 0.8), theme.green);
+        assert_eq!(progress_color(0.8, &theme, 0.8), theme.yellow);
+        assert_eq!(progress_color(0.99, &theme, 0.8), theme.yellow);
+        assert_eq!(progress_color(1.0, &theme, 0.8), theme.red);
+        assert_eq!(progress_color(0.5, &theme, 0.5), theme.yellow);
+    }
+
+    #[test]
+    fn toggling_show_seconds_switches_the_task_timer_text() {
+        let mut features = Features::default();
+        let mut timer = Timer::new(25);
+        timer.accumulated_time = chrono::Duration::seconds(5 * 60 + 30);
+        assert!(features.show_seconds);
+        assert_eq!(task_timer_text(&timer, &features), "19:30");
+
+        assert!(!features.toggle_show_seconds());
+        assert_eq!(task_timer_text(&timer, &features), "19m");
+        assert!(features.toggle_show_seconds());
+        assert_eq!(task_timer_text(&timer, &features), "19:30");
+    }
+
+    #[test]
+    fn truncation_counts_display_columns() {
+        assert_eq!(display_width("Write report"), 12);
+        assert_eq!(display_width("日本語テキスト"), 14);
+        assert_eq!(display_width("🍅🍅🍅 focus"), 12);
+
+        for (text, expected) in [
+            ("Write report", "Write…"),
+            ("日本語テキスト", "日本…"),
+            ("🍅🍅🍅 focus", "🍅🍅…"),
+        ] {
+            let truncated = truncate_with_ellipsis(text, 6);
+            assert_eq!(truncated, expected);
+            assert!(display_width(&truncated) <= 6);
+        }
+        assert_eq!(truncate_with_ellipsis("日本語", 6), "日本語");
+    }
+
+    #[test]
+    fn each_priority_maps_to_its_theme_color() {
+        let theme = Theme::default();
+        assert_eq!(priority_color(&Priority::Urgent, &theme), theme.red);
+        assert_eq!(priority_color(&Priority::High, &theme), theme.yellow);
+        assert_eq!(priority_color(&Priority::Medium, &theme), theme.cyan);
+        assert_eq!(priority_color(&Priority::Low, &theme), theme.gray);
+    }
+
+    #[test]
+    fn hiding_the_global_timer_gives_its_rows_to_the_tasks() {
+        let area = Rect::new(0, 0, 80, 30);
+        let heights = |show_global_timer| -> Vec<u16> {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(main_constraints(show_global_timer))
+                .split(area)
+                .iter()
+                .map(|chunk| chunk.height)
+                .collect()
+        };
+        assert_eq!(heights(true), [3, 5, 19, 3]);
+        assert_eq!(heights(false), [3, 0, 24, 3]);
+    }
+
+    #[test]
+    fn zero_target_shows_a_label_instead_of_a_bar() {
+        let icons = Icons::default();
+        let mut timer = Timer::new(20);
+        timer.accumulated_time = chrono::Duration::minutes(5);
+        let bar = task_progress_bar(&timer, &icons);
+        assert!(bar.starts_with(&icons.progress_filled.repeat(2)));
+        assert_ne!(bar, OPEN_ENDED_LABEL);
+
+        timer.target_duration = chrono::Duration::zero();
+        assert_eq!(task_progress_bar(&timer, &icons), OPEN_ENDED_LABEL);
+        assert_eq!(task_timer_text(&timer, &Features::default()), "↑05:00");
+    }
+
+    #[test]
+    fn long_timers_show_hours_and_minutes() {
+        assert_eq!(format_minutes(45, true), "45m");
+        assert_eq!(format_minutes(60, true), "1h 00m");
+        assert_eq!(format_minutes(90, true), "1h 30m");
+        assert_eq!(format_minutes(90, false), "90m");
+        assert_eq!(format_minutes(5, false), "05m");
+
+        let features = Features {
+            show_seconds: false,
+            show_hours: true,
+            ..Features::default()
+        };
+        assert_eq!(task_timer_text(&Timer::new(90), &features), "1h 30m");
+        assert_eq!(task_timer_text(&Timer::new(45), &features), "45m");
+    }
+}
@@ -0,0 +1,296 @@
+//! Thin HTTP+JSON bridge to the kronos IPC socket, for tooling that can't
+//! speak Unix sockets.
+//!
+//! Routes:
+//!   GET  /status   -> Command::Status
+//!   GET  /tasks    -> Command::ListTasks
+//!   POST /task     -> Command::AddTask, body `{"description": "..."}` plus
+//!                     optional "category", "minutes" and "priority"
+//!   POST /command  -> any one-shot `Command`, body in its serde JSON form;
+//!                     `Subscribe` and `Hello` get a 400
+//!
+//! Every call answers with the `Response` as JSON. A `Response::Error` from
+//! kronos means it rejected the command and comes back as a 422.
+
+use anyhow::Result;
+use clap::Parser;
+use kronos_ipc::{Command, Response};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::TcpListener;
+
+/// Largest request body accepted; anything bigger gets a 413.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// A status line and message to answer a request with instead of proxying it.
+type Rejection = (&'static str, String);
+
+#[derive(Parser)]
+#[command(name = "kronos-http")]
+#[command(about = "Expose the Kronos IPC commands over HTTP", long_about = None)]
+struct Cli {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:7878")]
+    listen: String,
+    /// IPC socket of the kronos instance to forward to
+    #[arg(short, long, default_value = kronos_ipc::SOCKET_PATH)]
+    socket: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct NewTask {
+    description: String,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    minutes: Option<i64>,
+    #[serde(default)]
+    priority: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let listener = TcpListener::bind(&cli.listen).await?;
+    println!("kronos-http listening on http://{}", cli.listen);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let socket = cli.socket.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &socket).await {
+                eprintln!("HTTP client error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    stream: impl AsyncRead + AsyncWrite + Unpin,
+    socket: &Path,
+) -> Result<()> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+
+    let command = read_request(&mut reader)
+        .await?
+        .and_then(|(method, path, body)| route(&method, &path, &body));
+    let (status, json) = match command {
+        Ok(command) => match kronos_ipc::send_command_to(socket, command).await {
+            Ok(response @ Response::Error(_)) => ("422 Unprocessable Entity", response),
+            Ok(response) => ("200 OK", response),
+            Err(e) => ("502 Bad Gateway", Response::Error(e.to_string())),
+        },
+        Err((status, message)) => (status, Response::Error(message)),
+    };
+
+    let payload = serde_json::to_vec(&json)?;
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        payload.len()
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(&payload).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+/// Reads the method, path and body of a request. A body over
+/// `MAX_BODY_BYTES` or a `Content-Length` that isn't a number is rejected
+/// without reading the body.
+async fn read_request(
+    reader: &mut (impl AsyncBufRead + Unpin),
+) -> Result<Result<(String, String, Vec<u8>), Rejection>> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                let Ok(length) = value.trim().parse() else {
+                    let message = format!("Invalid Content-Length: {:?}", value.trim());
+                    return Ok(Err(("400 Bad Request", message)));
+                };
+                content_length = length;
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        let message = format!("Body over the {} byte limit", MAX_BODY_BYTES);
+        return Ok(Err(("413 Payload Too Large", message)));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Ok((method, path, body)))
+}
+
+/// Maps a request onto the IPC command it stands for.
+fn route(method: &str, path: &str, body: &[u8]) -> Result<Command, Rejection> {
+    let bad_request = |e: serde_json::Error| ("400 Bad Request", e.to_string());
+    match (method, path) {
+        ("GET", "/status") => Ok(Command::Status),
+        ("GET", "/tasks") => Ok(Command::ListTasks),
+        ("POST", "/task") => {
+            let task: NewTask = serde_json::from_slice(body).map_err(bad_request)?;
+            Ok(Command::AddTask {
+                description: task.description,
+                category: task.category,
+                minutes: task.minutes,
+                priority: task.priority,
+            })
+        }
+        ("POST", "/command") => match serde_json::from_slice(body).map_err(bad_request)? {
+            // Subscribe streams forever and Hello is the proxy's own
+            // handshake; neither fits one request and one response.
+            Command::Subscribe | Command::Hello { .. } => Err((
+                "400 Bad Request",
+                "Subscribe and Hello can't be sent over HTTP".to_string(),
+            )),
+            command => Ok(command),
+        },
+        _ => Err(("404 Not Found", format!("No route for {} {}", method, path))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kronos_ipc::{TimerState, TimerStatus};
+    use tokio::net::UnixListener;
+
+    /// Sends `request` to the bridge and returns the raw HTTP response.
+    async fn exchange(request: &str, socket: &Path) -> String {
+        let (mut client, server) = tokio::io::duplex(MAX_BODY_BYTES);
+        let (handled, response) = tokio::join!(handle_connection(server, socket), async {
+            client.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            client.read_to_string(&mut response).await.unwrap();
+            response
+        });
+        handled.unwrap();
+        response
+    }
+
+    /// Answers one connection on `listener` like kronos would, replying
+    /// `reply` to whatever command comes after the handshake. Returns that
+    /// command.
+    async fn mock_kronos(listener: UnixListener, reply: Response) -> Command {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let hello = lines.next_line().await.unwrap().unwrap();
+        assert!(matches!(
+            serde_json::from_str(&hello).unwrap(),
+            Command::Hello { .. }
+        ));
+        let welcome = Response::Hello {
+            version: kronos_ipc::PROTOCOL_VERSION.to_string(),
+        };
+        writer
+            .write_all(&serde_json::to_vec(&welcome).unwrap())
+            .await
+            .unwrap();
+        writer.write_all(b"\n").await.unwrap();
+
+        let command = lines.next_line().await.unwrap().unwrap();
+        writer
+            .write_all(&serde_json::to_vec(&reply).unwrap())
+            .await
+            .unwrap();
+        serde_json::from_str(&command).unwrap()
+    }
+
+    /// `exchange` against a mock kronos on a fresh socket named `name`.
+    async fn exchange_with_kronos(request: &str, name: &str, reply: Response) -> (String, Command) {
+        let socket =
+            std::env::temp_dir().join(format!("kronos-http-{}-{}.sock", name, std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+        let result = tokio::join!(exchange(request, &socket), mock_kronos(listener, reply));
+        std::fs::remove_file(&socket).unwrap();
+        result
+    }
+
+    #[tokio::test]
+    async fn status_route_proxies_to_kronos() {
+        let reply = Response::Status(TimerStatus {
+            state: TimerState::Running,
+            elapsed: 60,
+            total: 1500,
+        });
+        let request = "GET /status HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let (response, command) = exchange_with_kronos(request, "status", reply).await;
+
+        assert!(matches!(command, Command::Status));
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        match serde_json::from_str(body).unwrap() {
+            Response::Status(status) => {
+                assert_eq!(status.state, TimerState::Running);
+                assert_eq!((status.elapsed, status.total), (60, 1500));
+            }
+            other => panic!("expected a status, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn unparseable_content_length_is_a_bad_request() {
+        let request = "POST /task HTTP/1.1\r\nContent-Length: lots\r\n\r\n";
+        let response = exchange(request, Path::new("/nonexistent")).await;
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    }
+
+    #[tokio::test]
+    async fn oversized_body_is_rejected_unread() {
+        let request = format!(
+            "POST /task HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_BYTES + 1
+        );
+        let response = exchange(&request, Path::new("/nonexistent")).await;
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+    }
+
+    #[tokio::test]
+    async fn streaming_commands_are_refused() {
+        for body in [r#""Subscribe""#, r#"{"Hello":{"version":"1.0"}}"#] {
+            let request = format!(
+                "POST /command HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let response = exchange(&request, Path::new("/nonexistent")).await;
+            assert!(
+                response.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_commands_are_client_errors() {
+        let body = r#"{"GetTask":{"id":99}}"#;
+        let request = format!(
+            "POST /command HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let reply = Response::Error("No task with id 99".to_string());
+        let (response, command) = exchange_with_kronos(&request, "rejected", reply).await;
+
+        assert!(matches!(command, Command::GetTask { id: 99 }));
+        assert!(response.starts_with("HTTP/1.1 422 Unprocessable Entity\r\n"));
+    }
+}
@@ -1,8 +1,15 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use kronos_ipc::{Command, Response, SOCKET_PATH};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::UnixStream;
+use directories::ProjectDirs;
+use kronos_ipc::{Command, CommandLogEntry, ImportFormat, Response, Task, TimerEvent, TimerStatus};
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 #[derive(Parser)]
 #[command(name = "kronosctl")]
@@ -10,6 +17,10 @@ use tokio::net::UnixStream;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Directory kronos keeps its data in, if not the default; takes
+    /// precedence over `KRONOS_DATA_DIR`
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -20,72 +31,618 @@ enum Commands {
     Pause,
     /// Resume the timer
     Resume,
-    /// Stop the timer
+    /// Stop the timer, keeping its elapsed time
     Stop,
     /// Reset the timer
     Reset,
     /// Get timer status
-    Status,
+    Status {
+        /// Print a one-line summary, e.g. "{state} {remaining}". Placeholders:
+        /// {state}, {elapsed}, {remaining}, {progress}, {total}
+        #[arg(short, long)]
+        format: Option<String>,
+    },
     /// Add a new task
     Task {
         #[arg(short, long)]
         add: Option<String>,
+        /// Category of the added task, e.g. Work or a custom name
+        #[arg(short, long, requires = "add")]
+        category: Option<String>,
+        /// Timer length of the added task in minutes
+        #[arg(short, long, requires = "add")]
+        minutes: Option<i64>,
+        /// Priority of the added task: low, medium, high or urgent
+        #[arg(short, long, requires = "add")]
+        priority: Option<String>,
+        /// Print the task with this id instead
+        #[arg(short, long, conflicts_with = "add")]
+        get: Option<u32>,
     },
     /// List all tasks
-    Tasks,
+    Tasks {
+        /// Only list tasks with fewer completed Pomodoros than estimated
+        #[arg(long)]
+        incomplete_pomodoros: bool,
+    },
+    /// Re-issue the last command sent
+    Repeat,
+    /// Import tasks from a JSON or CSV file
+    Import {
+        #[arg(short, long)]
+        file: PathBuf,
+    },
+    /// Show the commands the server has processed recently
+    History,
+    /// Start the timers of all incomplete tasks
+    StartAll {
+        /// Only start tasks in this category
+        #[arg(short, long)]
+        category: Option<String>,
+    },
+    /// Show minutes worked per category on completed tasks
+    Breakdown {
+        /// Print the data as JSON, e.g. for charting tools
+        #[arg(long)]
+        json: bool,
+    },
+    /// Select a task in the TUI
+    Select {
+        /// Id of the task to select, as shown by `kronosctl tasks`
+        #[arg(short, long)]
+        task: u32,
+    },
+    /// Print the TUI key bindings as a Markdown cheatsheet
+    Keys,
+    /// Check that kronos is reachable and speaks our protocol, changing nothing
+    Validate,
+    /// List the timer presets
+    Presets,
+    /// Set a task's timer from a preset
+    Preset {
+        /// Id of the task, as shown by `kronosctl tasks`
+        #[arg(short, long)]
+        task: u32,
+        /// Name of the preset, as shown by `kronosctl presets`
+        #[arg(short, long)]
+        name: String,
+    },
+    /// Tick or untick a subtask
+    Subtask {
+        /// Id of the task, as shown by `kronosctl tasks`
+        #[arg(short, long)]
+        task: u32,
+        /// Position of the subtask in the task, counting from 0
+        #[arg(short, long)]
+        index: usize,
+    },
+    /// Print the task to work on next
+    Suggest,
+    /// Print the versions of kronosctl and the running kronos
+    Version,
+    /// Print timer events (started, paused, completed) as they happen
+    Watch {
+        /// Print each event as a line of JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    
+    let status_format = match &cli.command {
+        Commands::Status { format } => format.clone(),
+        _ => None,
+    };
+    let json = matches!(cli.command, Commands::Breakdown { json: true });
+
     // Convert CLI command to IPC command
     let command = match cli.command {
+        Commands::Repeat => match load_last_command(&history_path(cli.data_dir.clone())?)? {
+            Some(command) => command,
+            None => {
+                println!("No previous command to repeat.");
+                return Ok(());
+            }
+        },
         Commands::Start => Command::Start,
         Commands::Pause => Command::Pause,
         Commands::Resume => Command::Resume,
         Commands::Stop => Command::Stop,
         Commands::Reset => Command::Reset,
-        Commands::Status => Command::Status,
-        Commands::Task { add: Some(desc) } => Command::AddTask { description: desc },
-        Commands::Task { add: None } => Command::ListTasks,
-        Commands::Tasks => Command::ListTasks,
+        Commands::Status { .. } => Command::Status,
+        Commands::Task {
+            add: Some(description),
+            category,
+            minutes,
+            priority,
+            ..
+        } => Command::AddTask {
+            description,
+            category,
+            minutes,
+            priority,
+        },
+        Commands::Task { get: Some(id), .. } => Command::GetTask { id },
+        Commands::Task { add: None, .. } => Command::ListTasks,
+        Commands::Tasks {
+            incomplete_pomodoros: false,
+        } => Command::ListTasks,
+        Commands::Tasks {
+            incomplete_pomodoros: true,
+        } => Command::FilterTasks {
+            incomplete_pomodoros: true,
+        },
+        Commands::Import { file } => {
+            let data = fs::read_to_string(&file)?;
+            let format = match file.extension().and_then(|e| e.to_str()) {
+                Some(ext) if ext.eq_ignore_ascii_case("csv") => ImportFormat::Csv,
+                _ => ImportFormat::Json,
+            };
+            Command::ImportTasks { format, data }
+        }
+        Commands::History => Command::History,
+        Commands::StartAll { category } => Command::StartAll { category },
+        Commands::Breakdown { .. } => Command::CategoryBreakdown,
+        Commands::Select { task } => Command::SelectTask { id: task },
+        Commands::Keys => Command::Keybindings,
+        Commands::Version => Command::Version,
+        Commands::Presets => Command::ListPresets,
+        Commands::Suggest => Command::SuggestTask,
+        Commands::Preset { task, name } => Command::ApplyPreset {
+            id: task,
+            preset: name,
+        },
+        Commands::Subtask { task, index } => Command::ToggleSubtask {
+            task_id: task,
+            subtask_index: index,
+        },
+        Commands::Validate => return validate().await,
+        Commands::Watch { json } => return watch(json).await,
     };
     
     // Send command and get response
-    let response = send_command(command).await?;
+    let response = send_command(command.clone()).await?;
+    if should_remember(&command, &response) {
+        // Repeat is a convenience; failing to save shouldn't fail the command.
+        let saved = history_path(cli.data_dir).and_then(|path| save_last_command(&path, &command));
+        if let Err(e) = saved {
+            eprintln!("Warning: couldn't save the command for repeat: {:#}", e);
+        }
+    }
     
     // Handle response
     match response {
         Response::Ok => println!("OK"),
-        Response::Status(status) => {
-            println!("State: {:?}", status.state);
-            println!("Elapsed: {}s", status.elapsed);
-        }
+        Response::Status(status) => match status_format {
+            Some(template) => println!("{}", render_status(&template, &status)?),
+            None => {
+                println!("State: {:?}", status.state);
+                println!("Elapsed: {}s", status.elapsed);
+            }
+        },
         Response::Tasks(tasks) => {
-            for task in tasks {
-                let check = if task.completed { "✓" } else { " " };
-                println!("[{}] {}: {}", check, task.id, task.description);
+            print!("{}", format_tasks(&tasks, io::stdout().is_terminal()));
+        }
+        Response::Task(task) | Response::Suggestion(Some(task)) => {
+            print!("{}", format_tasks(&[task], io::stdout().is_terminal()));
+        }
+        Response::Suggestion(None) => println!("Nothing left to work on"),
+        Response::Imported { imported, skipped } => {
+            println!("Imported {} task(s), skipped {}", imported, skipped);
+        }
+        Response::History(entries) => print!("{}", format_history(&entries)),
+        Response::Hello { version } | Response::Pong { version } => {
+            println!("Pong (protocol {})", version)
+        }
+        Response::Started { count } => println!("Started {} timer(s)", count),
+        Response::CategoryBreakdown(rows) if json => {
+            println!("{}", serde_json::to_string_pretty(&rows)?)
+        }
+        Response::CategoryBreakdown(rows) => {
+            let width = rows
+                .iter()
+                .map(|(c, _)| c.chars().count())
+                .max()
+                .unwrap_or(0);
+            for (category, minutes) in rows {
+                println!("{:<width$}  {}m", category, minutes, width = width);
             }
         }
+        Response::Event(event) => println!("{}", format_event(&event)),
+        Response::Cheatsheet(text) => print!("{}", text),
+        Response::Presets(presets) => {
+            let width = presets
+                .iter()
+                .map(|(name, _)| name.chars().count())
+                .max()
+                .unwrap_or(0);
+            for (name, minutes) in presets {
+                println!("{:<width$}  {}m", name, minutes, width = width);
+            }
+        }
+        Response::Version { version, protocol } => {
+            println!("kronos {} (protocol {})", version, protocol);
+            println!(
+                "kronosctl {} (protocol {})",
+                env!("CARGO_PKG_VERSION"),
+                kronos_ipc::PROTOCOL_VERSION
+            );
+        }
         Response::Error(e) => eprintln!("Error: {}", e),
     }
     
     Ok(())
 }
 
+fn format_seconds(seconds: u64) -> String {
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            (seconds % 3600) / 60,
+            seconds % 60
+        )
+    } else {
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Substitutes `{placeholder}`s in `template` with values from `status`.
+fn render_status(template: &str, status: &TimerStatus) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow::anyhow!("Unclosed '{{' in format: {}", template))?
+            + start;
+        let value = match &rest[start + 1..end] {
+            "state" => format!("{:?}", status.state),
+            "elapsed" => format_seconds(status.elapsed),
+            "remaining" => format_seconds(status.total.saturating_sub(status.elapsed)),
+            "total" => format_seconds(status.total),
+            "progress" => {
+                let progress = (status.elapsed * 100)
+                    .checked_div(status.total)
+                    .unwrap_or(0)
+                    .min(100);
+                format!("{}%", progress)
+            }
+            other => anyhow::bail!("Unknown placeholder {{{}}} in format", other),
+        };
+        out.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const CYAN: &str = "\x1b[36m";
+
+/// Renders tasks as aligned columns, with ANSI colors when `color` is set.
+fn format_tasks(tasks: &[Task], color: bool) -> String {
+    let id_width = tasks
+        .iter()
+        .map(|t| t.id.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let category_width = tasks
+        .iter()
+        .map(|t| t.category.chars().count())
+        .max()
+        .unwrap_or(0);
+    let priority_width = tasks
+        .iter()
+        .map(|t| t.priority.chars().count())
+        .max()
+        .unwrap_or(0);
+    let pomodoros = |t: &Task| {
+        if t.estimated_pomodoros > 0 {
+            format!("{}/{}", t.completed_pomodoros, t.estimated_pomodoros)
+        } else {
+            "-".to_string()
+        }
+    };
+    let pomodoro_width = tasks.iter().map(|t| pomodoros(t).len()).max().unwrap_or(0);
+    let paint = |text: String, code: &str| {
+        if color {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text
+        }
+    };
+
+    let mut out = String::new();
+    for task in tasks {
+        let check = if task.completed { "✓" } else { " " };
+        let priority_color = match task.priority.as_str() {
+            "Urgent" => RED,
+            "High" => YELLOW,
+            "Medium" => BLUE,
+            _ => DIM,
+        };
+        let description = if task.completed {
+            paint(task.description.clone(), DIM)
+        } else {
+            task.description.clone()
+        };
+        out.push_str(&format!(
+            "{} [{}] {} {} {} {}\n",
+            paint(format!("{:>width$}", task.id, width = id_width), DIM),
+            paint(check.to_string(), GREEN),
+            paint(
+                format!("{:<width$}", task.category, width = category_width),
+                CYAN
+            ),
+            paint(
+                format!("{:<width$}", task.priority, width = priority_width),
+                priority_color
+            ),
+            paint(
+                format!("{:>width$}", pomodoros(task), width = pomodoro_width),
+                DIM
+            ),
+            description,
+        ));
+    }
+    out
+}
+
+/// Lists history entries oldest first, each with how long ago it ran.
+fn format_history(entries: &[CommandLogEntry]) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut out = String::new();
+    for entry in entries {
+        let command = match &entry.command {
+            // The file contents would swamp the listing.
+            Command::ImportTasks { format, data } => {
+                format!(
+                    "ImportTasks {{ format: {:?}, {} bytes }}",
+                    format,
+                    data.len()
+                )
+            }
+            other => format!("{:?}", other),
+        };
+        out.push_str(&format!(
+            "{:>8} ago  {}\n",
+            format_seconds(now.saturating_sub(entry.timestamp)),
+            command
+        ));
+    }
+    out
+}
+
+/// Pings the server and exits nonzero if it's unreachable or on another version.
+async fn validate() -> Result<()> {
+    match send_command(Command::Ping).await {
+        Ok(Response::Pong { version }) => {
+            println!("OK: kronos is reachable (protocol {})", version);
+            Ok(())
+        }
+        Ok(other) => {
+            eprintln!("Unexpected response to ping: {:?}", other);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("kronos check failed: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Streams events until kronos exits.
+async fn watch(json: bool) -> Result<()> {
+    let mut subscription = kronos_ipc::subscribe().await?;
+    while let Some(event) = subscription.next_event().await? {
+        if json {
+            println!("{}", serde_json::to_string(&event)?);
+        } else {
+            println!("{}", format_event(&event));
+        }
+    }
+    Ok(())
+}
+
+fn format_event(event: &TimerEvent) -> String {
+    let (what, id) = match event {
+        TimerEvent::Started { id } => ("started", id),
+        TimerEvent::Paused { id } => ("paused", id),
+        TimerEvent::Stopped { id } => ("stopped", id),
+        TimerEvent::Completed { id } => ("completed", id),
+    };
+    if *id == 0 {
+        format!("global timer {}", what)
+    } else {
+        format!("task {} {}", id, what)
+    }
+}
+
 async fn send_command(cmd: Command) -> Result<Response> {
-    let mut stream = UnixStream::connect(SOCKET_PATH).await?;
-    
-    // Send command
-    let msg = serde_json::to_vec(&cmd)?;
-    stream.write_all(&msg).await?;
-    stream.write_all(b"\n").await?;
-    
-    // Read response
-    let mut buf = vec![0; 1024];
-    let n = stream.read(&mut buf).await?;
-    let response: Response = serde_json::from_slice(&buf[..n])?;
-    
-    Ok(response)
+    Ok(kronos_ipc::send_command(cmd).await?)
+}
+
+/// Where kronos keeps its data: `flag`, else `KRONOS_DATA_DIR`, else the
+/// platform data dir, else `$XDG_DATA_HOME/kronos`, else `~/.kronos`. The
+/// same chain as kronos itself, so `repeat` history lives next to its state.
+fn data_dir(flag: Option<PathBuf>) -> Result<PathBuf> {
+    let project_dir =
+        ProjectDirs::from("com", "pabloagn", "Kronos").map(|dirs| dirs.data_dir().to_path_buf());
+    resolve_data_dir(
+        flag,
+        env::var_os("KRONOS_DATA_DIR"),
+        project_dir,
+        env::var_os("XDG_DATA_HOME"),
+        env::var_os("HOME"),
+    )
+    .ok_or_else(|| anyhow::anyhow!("No data directory found (set HOME or XDG_DATA_HOME)."))
+}
+
+/// Picks the first usable directory of the `data_dir` chain.
+fn resolve_data_dir(
+    flag: Option<PathBuf>,
+    env_override: Option<OsString>,
+    project_dir: Option<PathBuf>,
+    xdg_data_home: Option<OsString>,
+    home: Option<OsString>,
+) -> Option<PathBuf> {
+    let non_empty = |v: Option<OsString>| v.filter(|v| !v.is_empty()).map(PathBuf::from);
+    flag.or_else(|| non_empty(env_override))
+        .or(project_dir)
+        .or_else(|| non_empty(xdg_data_home).map(|dir| dir.join("kronos")))
+        .or_else(|| non_empty(home).map(|dir| dir.join(".kronos")))
+}
+
+fn history_path(data_dir_flag: Option<PathBuf>) -> Result<PathBuf> {
+    Ok(data_dir(data_dir_flag)?.join("last_command.json"))
+}
+
+/// Whether `repeat` should re-issue `command`: not if it failed, and not an
+/// import, whose file contents would be sent again even if the file changed.
+fn should_remember(command: &Command, response: &Response) -> bool {
+    !matches!(response, Response::Error(_)) && !matches!(command, Command::ImportTasks { .. })
+}
+
+fn save_last_command(path: &Path, cmd: &Command) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_vec(cmd)?)?;
+    Ok(())
+}
+
+fn load_last_command(path: &Path) -> Result<Option<Command>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read(path)?;
+    Ok(Some(serde_json::from_slice(&json)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_command_loads_back_unchanged() {
+        let path = env::temp_dir()
+            .join(format!("kronosctl-test-{}", std::process::id()))
+            .join("last_command.json");
+        let command = Command::AddTask {
+            description: "Write report".to_string(),
+            category: Some("Work".to_string()),
+            minutes: Some(50),
+            priority: None,
+        };
+        save_last_command(&path, &command).unwrap();
+        let loaded = load_last_command(&path).unwrap().unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", command));
+    }
+
+    #[test]
+    fn failed_commands_and_imports_are_not_remembered() {
+        let error = Response::Error("No such task".to_string());
+        assert!(should_remember(&Command::Start, &Response::Ok));
+        assert!(!should_remember(&Command::Start, &error));
+        let import = Command::ImportTasks {
+            format: ImportFormat::Json,
+            data: "[]".to_string(),
+        };
+        assert!(!should_remember(&import, &Response::Ok));
+    }
+
+    #[test]
+    fn data_dir_prefers_flag_then_env_then_platform_dirs() {
+        let resolve = |flag: Option<&str>, env: Option<&str>, platform: Option<&str>| {
+            resolve_data_dir(
+                flag.map(PathBuf::from),
+                env.map(OsString::from),
+                platform.map(PathBuf::from),
+                None,
+                Some(OsString::from("/home/me")),
+            )
+        };
+        let flag = Some("/flag");
+        let platform = Some("/platform");
+        assert_eq!(resolve(flag, Some("/env"), platform), Some("/flag".into()));
+        assert_eq!(resolve(None, Some("/env"), platform), Some("/env".into()));
+        assert_eq!(resolve(None, Some(""), platform), Some("/platform".into()));
+        assert_eq!(resolve(None, None, None), Some("/home/me/.kronos".into()));
+    }
+
+    fn task(id: u32, description: &str, completed: bool) -> Task {
+        Task {
+            id,
+            description: description.to_string(),
+            completed,
+            category: "Work".to_string(),
+            priority: "Urgent".to_string(),
+            elapsed_seconds: 0,
+            estimated_pomodoros: 4,
+            completed_pomodoros: 1,
+        }
+    }
+
+    #[test]
+    fn format_tasks_aligns_plain_columns() {
+        let tasks = [task(9, "Write report", false), task(10, "Send it", true)];
+        assert_eq!(
+            format_tasks(&tasks, false),
+            " 9 [ ] Work Urgent 1/4 Write report\n10 [✓] Work Urgent 1/4 Send it\n"
+        );
+    }
+
+    #[test]
+    fn format_tasks_colors_only_when_asked() {
+        let tasks = [task(1, "Write report", false)];
+        let colored = format_tasks(&tasks, true);
+        assert!(colored.contains(&format!("{}Urgent{}", RED, RESET)));
+        assert!(colored.contains(&format!("{}Work{}", CYAN, RESET)));
+        assert!(!format_tasks(&tasks, false).contains('\x1b'));
+    }
+
+    fn status(elapsed: u64, total: u64) -> TimerStatus {
+        TimerStatus {
+            state: kronos_ipc::TimerState::Running,
+            elapsed,
+            total,
+        }
+    }
+
+    #[test]
+    fn render_status_fills_placeholders() {
+        let status = status(15 * 60, 25 * 60);
+        assert_eq!(
+            render_status("{state} {remaining} left of {total}", &status).unwrap(),
+            "Running 10:00 left of 25:00"
+        );
+        assert_eq!(render_status("{progress}", &status).unwrap(), "60%");
+        assert_eq!(render_status("{elapsed}", &status).unwrap(), "15:00");
+    }
+
+    #[test]
+    fn render_status_progress_is_capped_and_safe_without_a_target() {
+        let overrun = status(90, 60);
+        assert_eq!(render_status("{progress}", &overrun).unwrap(), "100%");
+        assert_eq!(render_status("{progress}", &status(90, 0)).unwrap(), "0%");
+    }
+
+    #[test]
+    fn render_status_rejects_unknown_and_unclosed_placeholders() {
+        assert!(render_status("{bogus}", &status(0, 60)).is_err());
+        assert!(render_status("{state", &status(0, 60)).is_err());
+    }
 }